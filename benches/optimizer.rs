@@ -0,0 +1,147 @@
+//! Benchmarks of the optimizer on plans of growing size.
+//!
+//! Each benchmark optimizes a plan with the default pipeline and reports the time,
+//! together with the total iterations and the largest egraph of all stages,
+//! so a new rule that blows up the egraph shows up even if the time is noisy.
+//!
+//! ```text
+//! cargo bench                 # run all benchmarks
+//! cargo bench -- joins        # run benchmarks whose names contain `joins`
+//! cargo test --benches        # run each benchmark once as a smoke test
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use sql_optimizer_labs::{OptimizeReport, Optimizer, RecExpr};
+
+/// The time to run each benchmark before measuring.
+const WARM_UP_TIME: Duration = Duration::from_millis(500);
+/// The time to measure each benchmark.
+const MEASUREMENT_TIME: Duration = Duration::from_secs(2);
+/// The maximum number of measured runs.
+const MAX_SAMPLES: usize = 100;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // `cargo bench` passes `--bench`, otherwise each benchmark runs once
+    let bench = args.iter().any(|a| a == "--bench");
+    let filter = args.into_iter().filter(|a| !a.starts_with("--")).collect();
+    let mut c = Bencher { bench, filter };
+
+    for n in [2, 3, 4, 6, 8] {
+        c.bench(&format!("joins/{n}"), &joins(n));
+    }
+    for n in [1, 2, 4, 8, 16] {
+        c.bench(&format!("conjuncts/{n}"), &conjuncts(n));
+    }
+    for n in [1, 4, 16, 64] {
+        c.bench(&format!("projection/{n}"), &projection(n));
+    }
+}
+
+struct Bencher {
+    /// Measure the time if true, otherwise run once.
+    bench: bool,
+    /// Only run benchmarks whose names contain any of them.
+    filter: Vec<String>,
+}
+
+impl Bencher {
+    /// Optimizes the plan repeatedly and prints the statistics.
+    fn bench(&mut self, name: &str, plan: &str) {
+        if !self.filter.is_empty() && !self.filter.iter().any(|f| name.contains(f.as_str())) {
+            return;
+        }
+        let expr: RecExpr = plan.parse().unwrap();
+        let optimize = || {
+            Optimizer::new()
+                .optimize(black_box(&expr))
+                .expect("failed to optimize")
+        };
+        let (_, report) = optimize();
+        if !self.bench {
+            println!("{name:<24} ok  {}", stats(&report));
+            return;
+        }
+
+        let start = Instant::now();
+        while start.elapsed() < WARM_UP_TIME {
+            optimize();
+        }
+        let mut samples = vec![];
+        let start = Instant::now();
+        while start.elapsed() < MEASUREMENT_TIME && samples.len() < MAX_SAMPLES {
+            let t = Instant::now();
+            optimize();
+            samples.push(t.elapsed());
+        }
+        samples.sort();
+        println!(
+            "{name:<24} time: [{:?} {:?} {:?}]  {}",
+            samples[0],
+            samples[samples.len() / 2],
+            samples[samples.len() - 1],
+            stats(&report)
+        );
+    }
+}
+
+/// Formats the iterations and the size of the largest egraph of all stages.
+fn stats(report: &OptimizeReport) -> String {
+    let max = |f: fn(&_) -> usize| report.stages.iter().map(f).max().unwrap_or(0);
+    format!(
+        "iterations: {}  enodes: {}  eclasses: {}",
+        report.iterations(),
+        max(|s| s.egraph_nodes),
+        max(|s| s.egraph_classes)
+    )
+}
+
+/// A chain of `n` tables joined by equality conditions, as a binder would produce:
+/// cross joins with all conditions in a filter above them.
+fn joins(n: usize) -> String {
+    let scan = |i: usize| format!("(scan t{i} (list t{i}.k t{i}.v))");
+    let join = (1..n).fold(scan(0), |left, i| {
+        format!("(join inner true {left} {})", scan(i))
+    });
+    let cond = (1..n)
+        .map(|i| format!("(= t{}.k t{i}.k)", i - 1))
+        .reduce(|a, b| format!("(and {a} {b})"))
+        .unwrap_or("true".into());
+    format!("(proj (list t0.v) (filter {cond} {join}))")
+}
+
+/// A filter with `n` conjuncts over a join, which are pushed down to both sides.
+fn conjuncts(n: usize) -> String {
+    let cond = (0..n)
+        .map(|i| match i % 3 {
+            0 => format!("(> a.v{} {i})", i % 4),
+            1 => format!("(< (+ b.v{} 1) {i})", i % 4),
+            _ => format!("(= a.v{} b.v{})", i % 4, (i + 1) % 4),
+        })
+        .fold("(= a.k b.k)".to_string(), |a, b| format!("(and {a} {b})"));
+    format!(
+        "(proj (list a.k) (filter {cond} (join inner true \
+            (scan a (list a.k a.v0 a.v1 a.v2 a.v3)) \
+            (scan b (list b.k b.v0 b.v1 b.v2 b.v3)))))"
+    )
+}
+
+/// A projection of `n` columns out of `2n` over a join, which prunes the unused columns.
+fn projection(n: usize) -> String {
+    let columns = |t: &str, range: std::ops::Range<usize>| {
+        range
+            .map(|i| format!("{t}.c{i}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        "(proj (list {} {}) (join inner (= a.c0 b.c0) \
+            (scan a (list {})) (scan b (list {}))))",
+        columns("a", 0..n.div_ceil(2)),
+        columns("b", 0..n / 2),
+        columns("a", 0..n),
+        columns("b", 0..n),
+    )
+}
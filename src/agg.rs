@@ -4,7 +4,7 @@ use super::*;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
-    // #[error("aggregate function calls cannot be nested")]
+    // #[error("aggregate function calls cannot be nested: {0}")]
     NestedAgg(String),
     // #[error("WHERE clause cannot contain aggregates")]
     AggInWhere,
@@ -12,8 +12,36 @@ pub enum Error {
     AggInGroupBy,
     // #[error("column {0} must appear in the GROUP BY clause or be used in an aggregate function")]
     ColumnNotInAgg(String),
+    // #[error("arguments to GROUPING must be grouping expressions: {0}")]
+    GroupingNotInGroupBy(String),
+    // #[error("ROLLUP, CUBE and GROUPING SETS cannot be nested")]
+    NestedGroupingSets,
+    // #[error("window function calls cannot be nested: {0}")]
+    NestedWindow(String),
+    // #[error("aggregate function calls cannot contain window function calls: {0}")]
+    WindowInAgg(String),
+    // #[error("WHERE clause cannot contain window functions")]
+    WindowInWhere,
+    // #[error("GROUP BY clause cannot contain window functions")]
+    WindowInGroupBy,
+    // #[error("HAVING clause cannot contain window functions")]
+    WindowInHaving,
+    // #[error("ORDER BY position {0} is not in select list")]
+    OrdinalOutOfRange(i32),
+    // #[error("column reference {0} is ambiguous")]
+    AmbiguousAlias(String),
+    // #[error("expected a list, found {0}")]
+    ExpectList(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+
+impl std::error::Error for Error {}
+
 /// Converts the SELECT statement into a plan tree.
 ///
 /// The nodes of all clauses have been added to the `egraph`.
@@ -0,0 +1,34 @@
+//! An interactive shell to try the optimizer.
+//!
+//! It reads plans as S-expressions from a file or stdin, optimizes them
+//! and prints the best plan, its cost and the statistics of each stage.
+//! A plan can span multiple lines. Lines starting with `.` are commands, see `.help`.
+//!
+//! ```text
+//! cargo run --bin repl [FILE]
+//! ```
+
+#![allow(unused)]
+
+const HELP: &str = "\
+Enter a plan as an S-expression, e.g. (filter (= a 1) (scan t (list a b)))
+
+.rules                        list the rule groups
+.rules enable|disable GROUP.. enable or disable rule groups
+.rules only GROUP..           enable only the rule groups
+.rules all                    enable all rule groups
+.explain on|off               print the plan as an operator tree
+.trace on|off                 print the rules applied to the plan
+.catalog                      list the primary keys
+.catalog load FILE            load primary keys from lines of `TABLE COLUMN..`
+.catalog clear                remove all primary keys
+.limits                       show the limits of each stage
+.limits node|iter N           set the maximum number of enodes or iterations
+.limits time SECONDS          set the maximum time
+.help                         show this message
+.quit                         exit
+";
+
+fn main() {
+    todo!()
+}
@@ -0,0 +1,12 @@
+//! Run test scripts of plans. See [`sql_optimizer_labs::slt`] for the format.
+//!
+//! ```text
+//! cargo run --bin slt [--override] PATH..
+//! ```
+//!
+//! A path can be a script or a directory of `*.slt` scripts.
+//! With `--override`, the expected output in the scripts is replaced by the actual output.
+
+fn main() {
+    todo!()
+}
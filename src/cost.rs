@@ -0,0 +1,40 @@
+//! Estimate the cardinality of plan nodes and the cost of plans.
+
+use egg::CostFunction;
+
+use super::*;
+
+/// The number of rows assumed for a table without statistics.
+pub const DEFAULT_TABLE_ROWS: f32 = 1000.0;
+
+/// The fraction of rows assumed to pass a non-constant predicate.
+pub const DEFAULT_SELECTIVITY: f32 = 0.1;
+
+/// The data type of cardinality analysis.
+///
+/// It is always 0 for non-plan nodes.
+pub type Rows = f32;
+
+/// Returns the estimated number of output rows of the node.
+pub fn analyze_rows(egraph: &EGraph, enode: &Expr) -> Rows {
+    todo!()
+}
+
+/// The cost function for plan extraction.
+///
+/// Expressions cost 1 per node and are evaluated once per input row.
+/// Plan nodes cost the rows they process.
+pub struct CostFn<'a> {
+    pub egraph: &'a EGraph,
+}
+
+impl CostFunction<Expr> for CostFn<'_> {
+    type Cost = f32;
+
+    fn cost<C>(&mut self, enode: &Expr, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(Id) -> Self::Cost,
+    {
+        todo!()
+    }
+}
@@ -0,0 +1,48 @@
+//! The error type of the crate.
+
+use super::*;
+
+/// An error raised when building, optimizing or executing a plan.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The plan is malformed, or a column can not be resolved.
+    Plan(validate::PlanError),
+    /// An aggregation or window function is used in a wrong place.
+    Agg(agg::Error),
+    /// A parameter has no value to bind.
+    Param(param::Error),
+    /// The plan fails to execute.
+    Eval(eval::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<validate::PlanError> for Error {
+    fn from(e: validate::PlanError) -> Self {
+        Error::Plan(e)
+    }
+}
+
+impl From<agg::Error> for Error {
+    fn from(e: agg::Error) -> Self {
+        Error::Agg(e)
+    }
+}
+
+impl From<param::Error> for Error {
+    fn from(e: param::Error) -> Self {
+        Error::Param(e)
+    }
+}
+
+impl From<eval::Error> for Error {
+    fn from(e: eval::Error) -> Self {
+        Error::Eval(e)
+    }
+}
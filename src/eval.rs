@@ -0,0 +1,56 @@
+//! Execute a plan on in-memory tables.
+//!
+//! The evaluator is a reference to check that an optimized plan returns the same rows
+//! as its input plan. It is simple rather than fast: every operator materializes its
+//! output, and all joins are nested loop joins.
+//!
+//! Expressions are resolved as in [`schema`]: an expression in the output of the child
+//! takes the value of that column, e.g. `(sum a)` above an aggregation.
+
+use std::collections::HashMap;
+
+use super::*;
+
+/// A row of values.
+pub type Row = Vec<Value>;
+
+/// An in-memory table.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Table {
+    /// The column names, which are matched with the columns of a scan
+    /// with or without the prefix of the table name or its alias, e.g. `t.a`, `t1.a` or `a`.
+    pub columns: Vec<Column>,
+    pub rows: Vec<Row>,
+}
+
+/// The tables by name.
+pub type Tables = HashMap<Column, Table>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    // #[error("table {0} not found")]
+    TableNotFound(String),
+    // #[error("column {0} not found")]
+    ColumnNotFound(String),
+    // #[error("division by zero")]
+    DivisionByZero,
+    // #[error("integer out of range: {0}")]
+    Overflow(String),
+    // #[error("invalid operand types: {0}")]
+    TypeMismatch(String),
+    // #[error("can not evaluate {0}")]
+    Unsupported(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Executes the plan on the tables and returns the output rows.
+pub fn execute(tables: &Tables, plan: &RecExpr) -> Result<Vec<Row>, Error> {
+    todo!()
+}
@@ -0,0 +1,65 @@
+//! Render a plan as an indented operator tree, like `EXPLAIN` in SQL.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::*;
+
+/// The explanation of a plan, which is displayed as an indented operator tree.
+///
+/// # Example
+/// ```text
+/// Projection [t1.a, t2.b]
+///   HashJoin inner on [t1.id = t2.id]
+///     Scan t1 [id, a]
+///     Scan t2 [id, b]
+/// ```
+///
+/// Each operator can be annotated with its estimated rows, cost and schema.
+/// In verbose mode, the expressions of an operator refer to the outputs of its children
+/// by index, e.g. `#0` is the first column of the input.
+pub struct Explain<'a> {
+    expr: &'a RecExpr,
+    analysis: ExprAnalysis,
+    rows: bool,
+    cost: bool,
+    schema: bool,
+    verbose: bool,
+}
+
+impl<'a> Explain<'a> {
+    /// Creates an explanation of the plan without annotations.
+    pub fn new(expr: &'a RecExpr) -> Self {
+        todo!()
+    }
+
+    /// Annotates each operator with the estimated number of rows.
+    pub fn with_rows(mut self) -> Self {
+        todo!()
+    }
+
+    /// Annotates each operator with the cost of its subtree.
+    pub fn with_cost(mut self) -> Self {
+        todo!()
+    }
+
+    /// Annotates each operator with its output expressions.
+    pub fn with_schema(mut self) -> Self {
+        todo!()
+    }
+
+    /// Shows the expressions with references to the input columns resolved to indices.
+    pub fn verbose(mut self) -> Self {
+        todo!()
+    }
+
+    /// Sets the analysis, which holds the catalog used to estimate the plan.
+    pub fn analysis(mut self, analysis: ExprAnalysis) -> Self {
+        todo!()
+    }
+}
+
+impl Display for Explain<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        todo!()
+    }
+}
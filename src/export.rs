@@ -0,0 +1,50 @@
+//! Export plans and egraphs to Graphviz DOT and JSON for visualization and external tools.
+//!
+//! # JSON format
+//!
+//! A plan is a tree of nodes:
+//!
+//! ```text
+//! {"op": "proj", "children": [{"op": "list", "children": [...]}, ...]}
+//! ```
+//!
+//! An egraph is a list of eclasses ordered by id. Each eclass has its enodes,
+//! whose children are eclass ids, and the analysis data:
+//!
+//! ```text
+//! {"classes": [
+//!   {"id": 0, "nodes": [{"op": "a", "children": []}],
+//!    "data": {"constant": null, "columns": ["a"], "schema": null}},
+//!   ...
+//! ]}
+//! ```
+//!
+//! - `constant` is the constant value in the plan syntax, e.g. `"1"`, `"'hello'"`, `"null"`,
+//!   or `null` if the expression is not a constant.
+//! - `columns` is sorted.
+//! - `schema` is the list of eclass ids, or `null` for non-plan nodes.
+
+use super::*;
+
+/// Returns the plan in Graphviz DOT format.
+pub fn plan_to_dot(expr: &RecExpr) -> String {
+    todo!()
+}
+
+/// Returns the plan in JSON format, or `null` if the plan is empty.
+pub fn plan_to_json(expr: &RecExpr) -> String {
+    todo!()
+}
+
+/// Returns the egraph in Graphviz DOT format.
+///
+/// Each eclass is a cluster labeled with its id and analysis data.
+/// The edges point from an enode to the cluster of its child.
+pub fn egraph_to_dot(egraph: &EGraph) -> String {
+    todo!()
+}
+
+/// Returns the egraph in JSON format.
+pub fn egraph_to_json(egraph: &EGraph) -> String {
+    todo!()
+}
@@ -0,0 +1,62 @@
+//! Join ordering by dynamic programming.
+//!
+//! Exploring all join orders in the egraph grows exponentially with the number of tables.
+//! This pass reorders joins outside of the egraph, so it can be applied to a [`RecExpr`]
+//! before or between saturation passes:
+//!
+//! ```text
+//! (filter (and (= t1.id t2.id) (= t2.k t3.k))       (proj (list t1.id t1.a t3.k t3.c t2.id t2.k)
+//!   (join inner true                                  (hashjoin inner (list t2.k) (list t3.k)
+//!     (join inner true                         =>       (hashjoin inner (list t1.id) (list t2.id)
+//!       (scan t1 (list t1.id t1.a))                        (scan t1 (list t1.id t1.a))
+//!       (scan t3 (list t3.k t3.c)))                        (scan t2 (list t2.id t2.k)))
+//!     (scan t2 (list t2.id t2.k))))                      (scan t3 (list t3.k t3.c))))
+//! ```
+//!
+//! A region of inner `join`s and `filter`s is flattened into a join graph,
+//! whose vertices are the inputs of the region and edges are the predicates.
+//! The cheapest order is the one with the least sum of intermediate result sizes.
+
+use std::fmt::Display;
+
+use super::*;
+
+/// The maximum number of relations ordered by dynamic programming.
+///
+/// Larger regions are ordered greedily.
+pub const DP_THRESHOLD: usize = 10;
+
+/// Reorders all regions of inner joins in `expr`.
+///
+/// The output columns of each region keep the original order.
+pub fn reorder_joins(expr: &RecExpr) -> RecExpr {
+    todo!()
+}
+
+/// Returns the `join`s in `expr` that are Cartesian products,
+/// i.e. no conjunct of the join condition references both sides.
+pub fn cross_products(expr: &RecExpr) -> Vec<Id> {
+    todo!()
+}
+
+/// Returns the regions of inner joins in `expr` whose join graph is not connected.
+///
+/// Such regions can not be planned without a Cartesian product.
+pub fn disconnected_relations(expr: &RecExpr) -> Vec<Disconnected> {
+    todo!()
+}
+
+/// A region of inner joins whose join graph is not connected.
+#[derive(Debug)]
+pub struct Disconnected {
+    /// The root of the region in the input expression.
+    pub root: Id,
+    /// The relations in each connected component.
+    pub components: Vec<Vec<RecExpr>>,
+}
+
+impl Display for Disconnected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
@@ -1,14 +1,29 @@
 #![allow(unused)]
 
+use std::collections::HashMap;
 use std::hash::Hash;
 
 use egg::{define_language, Analysis, DidMerge, Id};
 
 pub mod agg;
+pub mod cost;
+pub mod error;
+pub mod eval;
+pub mod explain;
+pub mod export;
 pub mod expr;
+pub mod join_order;
+pub mod optimizer;
+pub mod param;
 pub mod plan;
+pub mod schema;
+pub mod slt;
+pub mod trace;
+pub mod validate;
 mod value;
 
+pub use error::Error;
+pub use optimizer::{OptimizeReport, Optimizer, Stage};
 pub use value::*;
 
 pub type RecExpr = egg::RecExpr<Expr>;
@@ -19,6 +34,7 @@ define_language! {
     pub enum Expr {
         // values
         Constant(Value),            // null, true, 1, 'hello'
+        Param(Param),               // $1, $2, ... a placeholder bound by `param::bind`
         Column(Column),             // t.a, b, c
 
         // TODO: add more nodes
@@ -26,15 +42,38 @@ define_language! {
 }
 
 /// The unified analysis for all rules.
-#[derive(Default)]
-pub struct ExprAnalysis;
+#[derive(Default, Clone)]
+pub struct ExprAnalysis {
+    /// The primary key columns of tables in the catalog.
+    pub primary_keys: HashMap<Column, Vec<Column>>,
+
+    /// True if the join graph of every region of inner joins in the plan is connected,
+    /// so join reordering never introduces a Cartesian product.
+    ///
+    /// It is set by the [`Optimizer`] with [`join_order::disconnected_relations`].
+    /// Otherwise a rotation may keep a Cartesian product that was in the original tree.
+    pub connected_joins: bool,
+
+    /// The conditions that passed for each rule, which are shown in the trace.
+    ///
+    /// They are recorded only if explanations are enabled in the egraph.
+    pub conditions: trace::Conditions,
+}
 
 /// The analysis data associated with each eclass.
 ///
 /// See [`egg::Analysis`] for how data is being processed.
 #[derive(Debug)]
 pub struct Data {
-    // TODO: add analysis data
+    /// All columns involved in the node.
+    pub columns: plan::ColumnSet,
+
+    /// The schema for plan node: a list of expressions.
+    ///
+    /// For non-plan node, it is always None.
+    /// For plan node, it may be None if the schema is unknown due to unresolved `prune`.
+    pub schema: schema::Schema,
+    // TODO: add more analysis data
 }
 
 impl Analysis<Expr> for ExprAnalysis {
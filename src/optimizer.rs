@@ -0,0 +1,251 @@
+//! The entry of the optimizer: run the rules on a plan and extract the best one.
+//!
+//! Running all rules together makes some of them interact and blow up the egraph,
+//! e.g. every `pushdown-proj-*` rule adds new `proj` nodes that feed `join-swap`.
+//! So the optimizer runs a pipeline of [`Stage`]s. Each stage runs a subset of the rules
+//! on a fresh egraph seeded with the best plan extracted from the previous stage.
+
+use std::time::Duration;
+
+use egg::{AstSize, Extractor, Runner, StopReason};
+
+use super::*;
+
+/// A group of rules that can be enabled in the [`Optimizer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleGroup {
+    /// Simplify expressions. See [`expr::rules`].
+    Expr,
+    /// Remove plan nodes that do nothing. See [`plan::cancel_rules`].
+    Cancel,
+    /// Merge adjacent plan nodes. See [`plan::merge_rules`].
+    Merge,
+    /// See [`plan::predicate_pushdown_rules`].
+    PredicatePushdown,
+    /// See [`plan::projection_pushdown_rules`].
+    ProjectionPushdown,
+    /// Reorder inner joins. See [`plan::join_reorder_rules`].
+    JoinReorder,
+    /// Convert logical joins to physical ones. See [`plan::join_rules`].
+    Join,
+    /// See [`plan::agg_pushdown_rules`].
+    AggPushdown,
+}
+
+impl RuleGroup {
+    /// All groups, which give the same rules as [`expr::rules`] and [`plan::rules`].
+    pub const ALL: [RuleGroup; 8] = [
+        RuleGroup::Expr,
+        RuleGroup::Cancel,
+        RuleGroup::Merge,
+        RuleGroup::PredicatePushdown,
+        RuleGroup::ProjectionPushdown,
+        RuleGroup::JoinReorder,
+        RuleGroup::Join,
+        RuleGroup::AggPushdown,
+    ];
+
+    /// Returns the rules in the group.
+    pub fn rules(self) -> Vec<Rewrite> {
+        todo!()
+    }
+}
+
+/// A stage of the optimization pipeline: a saturation run of some rule groups.
+///
+/// The limits are the same as [`egg::Runner`] by default.
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub name: &'static str,
+    pub rule_groups: Vec<RuleGroup>,
+    /// Whether to order the joins by [`join_order::reorder_joins`] before running the rules.
+    pub join_order: bool,
+    pub node_limit: usize,
+    pub iter_limit: usize,
+    pub time_limit: Duration,
+}
+
+impl Stage {
+    /// Creates a stage running the rule groups.
+    pub fn new(name: &'static str, rule_groups: &[RuleGroup]) -> Self {
+        todo!()
+    }
+
+    /// Orders the joins by dynamic programming before running the rules,
+    /// if [`RuleGroup::JoinReorder`], [`RuleGroup::PredicatePushdown`] and [`RuleGroup::Join`]
+    /// are enabled, since it also pushes down the predicates and chooses hash joins.
+    ///
+    /// The rules then start from a good order instead of exploring all of them,
+    /// which keeps the egraph small for queries with many joins.
+    pub fn join_order(mut self) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum number of enodes in the egraph.
+    pub fn node_limit(mut self, limit: usize) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum number of iterations.
+    pub fn iter_limit(mut self, limit: usize) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum time to run the rules.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        todo!()
+    }
+
+    /// Returns the default pipeline:
+    ///
+    /// 1. order joins by dynamic programming and simplify expressions
+    /// 2. push down predicates, which are split by the expression rules
+    /// 3. reorder joins and choose physical joins, since the order depends on their cost
+    /// 4. push down projections and prune columns
+    pub fn default_pipeline() -> Vec<Stage> {
+        todo!()
+    }
+}
+
+/// How to extract the best plan from the egraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Extraction {
+    /// The plan with the lowest [`cost::CostFn`].
+    #[default]
+    Cost,
+    /// The plan with the fewest nodes.
+    AstSize,
+}
+
+/// The optimizer, configured by the builder methods.
+///
+/// By default, it runs the [default pipeline](Stage::default_pipeline) with all rule groups enabled.
+#[derive(Clone)]
+pub struct Optimizer {
+    stages: Vec<Stage>,
+    rule_groups: Vec<RuleGroup>,
+    extraction: Extraction,
+    analysis: ExprAnalysis,
+    trace: bool,
+}
+
+/// The statistics of an optimization.
+#[derive(Debug, Clone)]
+pub struct OptimizeReport {
+    /// The statistics of each stage that has been run.
+    pub stages: Vec<StageReport>,
+    /// The cost of the input plan.
+    pub cost_before: f32,
+    /// The cost of the extracted plan.
+    pub cost_after: f32,
+    /// The rules applied to derive the extracted plan from the input,
+    /// if enabled by [`Optimizer::with_trace`].
+    pub trace: Vec<trace::TraceStep>,
+}
+
+/// The statistics of a stage.
+#[derive(Debug, Clone)]
+pub struct StageReport {
+    /// The name of the stage.
+    pub name: &'static str,
+    /// The number of iterations of the runner.
+    pub iterations: usize,
+    /// The number of enodes in the egraph when the runner stopped.
+    pub egraph_nodes: usize,
+    /// The number of eclasses in the egraph when the runner stopped.
+    pub egraph_classes: usize,
+    /// Why the runner stopped.
+    pub stop_reason: StopReason,
+    /// The cost of the plan extracted from the stage.
+    pub cost: f32,
+}
+
+impl OptimizeReport {
+    /// Returns the total number of iterations of all stages.
+    pub fn iterations(&self) -> usize {
+        todo!()
+    }
+
+    /// Returns the trace as a step-by-step rewrite log.
+    pub fn trace_log(&self) -> String {
+        todo!()
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        todo!()
+    }
+}
+
+impl Optimizer {
+    /// Creates an optimizer with the default options.
+    pub fn new() -> Self {
+        todo!()
+    }
+
+    /// Sets the stages of the pipeline.
+    ///
+    /// A single stage with all rule groups saturates all rules together.
+    pub fn stages(mut self, stages: Vec<Stage>) -> Self {
+        todo!()
+    }
+
+    /// Sets the enabled rule groups. Other groups are not run in any stage.
+    pub fn rule_groups(mut self, groups: &[RuleGroup]) -> Self {
+        todo!()
+    }
+
+    /// Enables a rule group.
+    pub fn enable(mut self, group: RuleGroup) -> Self {
+        todo!()
+    }
+
+    /// Disables a rule group.
+    pub fn disable(mut self, group: RuleGroup) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum number of enodes in the egraph of every stage.
+    pub fn node_limit(mut self, limit: usize) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum number of iterations of every stage.
+    pub fn iter_limit(mut self, limit: usize) -> Self {
+        todo!()
+    }
+
+    /// Sets the maximum time to run the rules of every stage.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        todo!()
+    }
+
+    /// Sets how to extract the best plan.
+    pub fn extraction(mut self, extraction: Extraction) -> Self {
+        todo!()
+    }
+
+    /// Sets the analysis, which holds the catalog used by the rules.
+    pub fn analysis(mut self, analysis: ExprAnalysis) -> Self {
+        todo!()
+    }
+
+    /// Records the rules applied to derive the optimized plan in the report.
+    ///
+    /// It enables explanations in the egraph, which makes the optimization slower.
+    pub fn with_trace(mut self) -> Self {
+        todo!()
+    }
+
+    /// Validates and optimizes the plan.
+    ///
+    /// Returns the best plan and the statistics of the optimization.
+    pub fn optimize(&self, expr: &RecExpr) -> Result<(RecExpr, OptimizeReport), Error> {
+        todo!()
+    }
+    /// Extracts the best plan of the eclass `root` and returns it with its cost.
+    fn extract(&self, egraph: &EGraph, root: Id) -> (f32, RecExpr) {
+        todo!()
+    }
+}
@@ -0,0 +1,41 @@
+//! Parameterized queries.
+//!
+//! A plan of a prepared statement contains placeholders `$1`, `$2`, ... instead of constants.
+//! Since a placeholder is not a constant, rules that depend on its value do not apply,
+//! and the plan can be optimized once and executed with different parameters:
+//!
+//! ```text
+//! (filter (= a (+ $1 1)) (scan t (list a b)))
+//!
+//! bind [1] => (filter (= a 2) (scan t (list a b)))
+//! ```
+
+use super::*;
+
+/// An error when binding parameters.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    // #[error("no value for parameter {0}")]
+    NotBound(Param),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Returns the distinct parameters in the plan in ascending order.
+pub fn params(plan: &RecExpr) -> Vec<Param> {
+    todo!()
+}
+
+/// Substitutes `values` for the parameters in the plan, where `$1` is `values[0]`,
+/// and folds the expressions that become constants.
+///
+/// The plan is usually optimized before binding, so other nodes are left unchanged.
+pub fn bind(plan: &RecExpr, values: &[Value]) -> Result<RecExpr, Error> {
+    todo!()
+}
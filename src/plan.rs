@@ -8,17 +8,78 @@ use egg::rewrite as rw;
 /// Returns the rules that always improve the plan.
 pub fn rules() -> Vec<Rewrite> {
     let mut rules = vec![];
+    rules.extend(cancel_rules());
+    rules.extend(merge_rules());
+    rules.extend(predicate_pushdown_rules());
     rules.extend(projection_pushdown_rules());
     rules.extend(join_rules());
+    rules.extend(join_reorder_rules());
+    rules.extend(agg_pushdown_rules());
     // TODO: add rules
     rules
 }
 
+/// Remove plan nodes that do nothing.
+#[rustfmt::skip]
+pub fn cancel_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
+/// Merge adjacent plan nodes.
+#[rustfmt::skip]
+pub fn merge_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
+/// Pushdown filters and split their conditions.
+#[rustfmt::skip]
+pub fn predicate_pushdown_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
 #[rustfmt::skip]
 pub fn join_rules() -> Vec<Rewrite> { vec![
     // TODO: add rules
 ]}
 
+/// Reorder inner joins.
+///
+/// Rotations in both directions together with swapping the children of a join
+/// reach all join orders. Since the rules do not decide which order is better,
+/// the plan should be extracted with [`CostFn`](crate::cost::CostFn).
+#[rustfmt::skip]
+pub fn join_reorder_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
+/// Pushdown partial aggregations below inner joins.
+///
+/// The aggregation is split into a partial one below the join and a final one above it,
+/// which reduces the rows to be joined. Since it is not always beneficial,
+/// the plan should be extracted with [`CostFn`](crate::cost::CostFn).
+#[rustfmt::skip]
+pub fn agg_pushdown_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
+/// Split aggregations into two phases for parallel or distributed execution.
+///
+/// The partial aggregation computes the states of aggregations on each partition of rows,
+/// and the final one merges them. Since the cost model does not consider parallelism,
+/// these rules are not included in [`rules`].
+#[rustfmt::skip]
+pub fn two_phase_agg_rules() -> Vec<Rewrite> { vec![
+    // TODO: add rules
+]}
+
+/// The data type of column analysis.
+pub type ColumnSet = HashSet<Column>;
+
+/// Returns all columns involved in the node.
+pub fn analyze_columns(egraph: &EGraph, enode: &Expr) -> ColumnSet {
+    todo!()
+}
+
 /// Pushdown projections and prune unused columns.
 #[rustfmt::skip]
 pub fn projection_pushdown_rules() -> Vec<Rewrite> { vec![
@@ -0,0 +1,13 @@
+//! Analyze schema and replace all column references with physical indices.
+//!
+//! This is the final step before executing.
+
+use super::*;
+
+/// The data type of schema analysis.
+pub type Schema = Option<Vec<Id>>;
+
+/// Returns the output expressions for plan node.
+pub fn analyze_schema(egraph: &EGraph, enode: &Expr) -> Schema {
+    todo!()
+}
@@ -0,0 +1,57 @@
+//! Run test scripts of plans in the style of [sqllogictest](https://www.sqlite.org/sqllogictest).
+//!
+//! A script is a sequence of records separated by blank lines.
+//! Lines starting with `#` are comments.
+//!
+//! ```text
+//! # a table with its columns, an optional primary key, and its rows
+//! table t (t.id t.v) key (t.id)
+//! 1 'a'
+//! 2 null
+//!
+//! # a query optimizes the plan and executes it, `rowsort` sorts the rows
+//! query rowsort
+//! (proj (list t.v) (filter (= t.id 1) (scan t (list t.id t.v))))
+//! ---- plan
+//! (proj (list t.v) (filter (= t.id 1) (scan t (list t.id t.v))))
+//! ---- result
+//! 'a'
+//! ```
+//!
+//! A query checks the sections that are present: the optimized plan, the rows it returns,
+//! or the `---- error` raised by the optimizer or the evaluator.
+//! The optimized plan must also return the same rows as the input plan, in any order.
+//!
+//! [`run`] returns the script with the expected sections replaced by the actual output,
+//! which can override the script when the changes are expected.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::*;
+
+/// A record in the script that does not pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The line number of the record, starting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The result of running a script.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub failures: Vec<Failure>,
+    /// The script with the actual output of all queries.
+    pub output: String,
+}
+
+/// Runs the script.
+pub fn run(script: &str) -> Outcome {
+    todo!()
+}
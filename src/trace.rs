@@ -0,0 +1,64 @@
+//! Trace the rules that derive the optimized plan from the input, using egg's explanations.
+
+use std::fmt::{self, Display, Formatter};
+
+use egg::{Condition, Subst, Symbol};
+
+use super::*;
+
+/// The conditions that passed for each rule, in the order they are checked.
+pub type Conditions = HashMap<Symbol, Vec<&'static str>>;
+
+/// A condition of a rule with its source text. It is created by `rw!`.
+///
+/// If explanations are enabled, the text is recorded in [`ExprAnalysis::conditions`]
+/// when the condition passes, so the trace shows the checks that ran in the egraph.
+pub(crate) struct Check<C> {
+    rule: Symbol,
+    text: &'static str,
+    condition: C,
+}
+
+impl<C> Check<C> {
+    pub(crate) fn new(rule: impl Into<Symbol>, text: &'static str, condition: C) -> Self {
+        todo!()
+    }
+}
+
+impl<C: Condition<Expr, ExprAnalysis>> Condition<Expr, ExprAnalysis> for Check<C> {
+    fn check(&self, egraph: &mut EGraph, eclass: Id, subst: &Subst) -> bool {
+        todo!()
+    }
+
+    fn vars(&self) -> Vec<egg::Var> {
+        todo!()
+    }
+}
+
+/// A step of the trace: a rule is applied to the plan of the previous step.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// The name of the optimizer stage.
+    pub stage: &'static str,
+    /// The name of the rule.
+    pub rule: String,
+    /// True if the rule is applied from its right-hand side to the left-hand side.
+    pub reverse: bool,
+    /// The conditions of the rule, which passed when it was applied.
+    pub conditions: Vec<&'static str>,
+    /// The plan after the rule is applied.
+    pub plan: RecExpr,
+}
+
+impl Display for TraceStep {
+    /// Displays the step as:
+    ///
+    /// ```text
+    /// [stage] rule
+    ///   if condition
+    ///   => plan
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        todo!()
+    }
+}
@@ -0,0 +1,70 @@
+//! Validate the structure of a plan before optimizing it.
+//!
+//! The rules and analyses assume that the input is well-formed, e.g. the first child of
+//! `proj` is a list, and may panic otherwise.
+
+use super::*;
+
+/// The path from the root to a node: the index of the child at each level.
+pub type Path = Vec<usize>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlanError {
+    // #[error("the plan is empty")]
+    Empty,
+    // #[error("expected a plan at {path:?}, found {found}")]
+    ExpectPlan {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected an expression at {path:?}, found {found}")]
+    ExpectExpr {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a list at {path:?}, found {found}")]
+    ExpectList {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a column at {path:?}, found {found}")]
+    ExpectColumn {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a join type at {path:?}, found {found}")]
+    ExpectJoinType {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected {expected} elements at {path:?}, found {found}")]
+    LengthMismatch {
+        path: Path,
+        expected: usize,
+        found: usize,
+    },
+    // #[error("column {column} not found at {path:?}")]
+    ColumnNotFound {
+        path: Path,
+        column: String,
+    },
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        todo!()
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Checks that the plan is well-formed:
+///
+/// - children in plan positions are plans, and the others are expressions
+/// - operands that must be lists are lists, with the same length if they are paired
+/// - columns used by a plan node are produced by its children
+///
+/// If the root is not a plan, it is validated as an expression without checking columns.
+pub fn validate(expr: &RecExpr) -> Result<(), PlanError> {
+    todo!()
+}
@@ -45,3 +45,25 @@ impl FromStr for Value {
 }
 
 pub type Column = egg::Symbol;
+
+/// A placeholder of a parameterized query, numbered from 1.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Param(pub u32);
+
+impl Display for Param {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+impl FromStr for Param {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = s
+            .strip_prefix('$')
+            .ok_or_else(|| "no leading $".to_string())?;
+        let num = body.parse().map_err(|e| format!("invalid number: {e}"))?;
+        Ok(Self(num))
+    }
+}
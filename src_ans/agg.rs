@@ -12,7 +12,14 @@ pub fn analyze_aggs(egraph: &EGraph, enode: &Expr) -> AggSet {
     use Expr::*;
     let x = |i: &Id| egraph[*i].data.aggs.clone();
     match enode {
-        Max(_) | Min(_) | Sum(_) | Avg(_) | Count(_) => vec![enode.clone()],
+        Max(_) | Min(_) | Sum(_) | Avg(_) | Count(_) | RowCount | CountDistinct(_)
+        | SumDistinct(_) | Grouping(_) => vec![enode.clone()],
+        // the window function itself is not an aggregation, but its arguments may be
+        Over([f, partition, order]) => (egraph[*f].nodes.iter())
+            .flat_map(|e| e.children().iter().flat_map(x))
+            .chain(x(partition))
+            .chain(x(order))
+            .collect(),
         // merge the set from all children
         Nested(_) | List(_) | Neg(_) | Not(_) | IsNull(_) | Add(_) | Sub(_) | Mul(_) | Div(_)
         | Eq(_) | NotEq(_) | Gt(_) | Lt(_) | GtEq(_) | LtEq(_) | And(_) | Or(_) | Xor(_)
@@ -22,9 +29,51 @@ pub fn analyze_aggs(egraph: &EGraph, enode: &Expr) -> AggSet {
     }
 }
 
+/// Returns the aggregations that compute the partial states of `agg`.
+///
+/// The states are computed by a partial [`Agg`](Expr::Agg) over a subset of rows,
+/// and merged by a [`FinalAgg`](Expr::FinalAgg) to produce the result of `agg`:
+///
+/// | agg        | states               | merge                        |
+/// |------------|----------------------|------------------------------|
+/// | `sum(a)`   | `sum(a)`             | `sum(sum(a))`                |
+/// | `count(a)` | `count(a)`           | `sum(count(a))`              |
+/// | `rowcount` | `rowcount`           | `sum(rowcount)`              |
+/// | `max(a)`   | `max(a)`             | `max(max(a))`                |
+/// | `min(a)`   | `min(a)`             | `min(min(a))`                |
+/// | `avg(a)`   | `sum(a)`, `count(a)` | `sum(sum(a)) / sum(count(a))` |
+///
+/// Returns `None` if the aggregation can not be split, e.g. `count(distinct a)`.
+pub fn agg_states(agg: &Expr) -> Option<Vec<Expr>> {
+    use Expr::*;
+    match agg {
+        Max(_) | Min(_) | Sum(_) | Count(_) | RowCount => Some(vec![agg.clone()]),
+        Avg(a) => Some(vec![Sum(*a), Count(*a)]),
+        _ => None,
+    }
+}
+
+/// Adds the states of all aggregations in the list `aggs` to the egraph,
+/// and returns the list of distinct states.
+///
+/// Returns `None` if `aggs` is not a list or any aggregation can not be split.
+pub fn add_agg_states(egraph: &mut EGraph, aggs: Id) -> Option<Id> {
+    let mut states = vec![];
+    for agg in egraph[aggs].as_list()?.to_vec() {
+        let exprs = egraph[agg].iter().find_map(agg_states)?;
+        for expr in exprs {
+            let id = egraph.add(expr);
+            if !states.contains(&id) {
+                states.push(id);
+            }
+        }
+    }
+    Some(egraph.add(Expr::List(states.into())))
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
-    // #[error("aggregate function calls cannot be nested")]
+    // #[error("aggregate function calls cannot be nested: {0}")]
     NestedAgg(String),
     // #[error("WHERE clause cannot contain aggregates")]
     AggInWhere,
@@ -32,8 +81,60 @@ pub enum Error {
     AggInGroupBy,
     // #[error("column {0} must appear in the GROUP BY clause or be used in an aggregate function")]
     ColumnNotInAgg(String),
+    // #[error("arguments to GROUPING must be grouping expressions: {0}")]
+    GroupingNotInGroupBy(String),
+    // #[error("ROLLUP, CUBE and GROUPING SETS cannot be nested")]
+    NestedGroupingSets,
+    // #[error("window function calls cannot be nested: {0}")]
+    NestedWindow(String),
+    // #[error("aggregate function calls cannot contain window function calls: {0}")]
+    WindowInAgg(String),
+    // #[error("WHERE clause cannot contain window functions")]
+    WindowInWhere,
+    // #[error("GROUP BY clause cannot contain window functions")]
+    WindowInGroupBy,
+    // #[error("HAVING clause cannot contain window functions")]
+    WindowInHaving,
+    // #[error("ORDER BY position {0} is not in select list")]
+    OrdinalOutOfRange(i32),
+    // #[error("column reference {0} is ambiguous")]
+    AmbiguousAlias(String),
+    // #[error("expected a list, found {0}")]
+    ExpectList(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::Error::*;
+        match self {
+            NestedAgg(e) => write!(f, "aggregate function calls cannot be nested: {e}"),
+            AggInWhere => write!(f, "WHERE clause cannot contain aggregates"),
+            AggInGroupBy => write!(f, "GROUP BY clause cannot contain aggregates"),
+            ColumnNotInAgg(c) => write!(
+                f,
+                "column {c} must appear in the GROUP BY clause or be used in an aggregate function"
+            ),
+            GroupingNotInGroupBy(e) => {
+                write!(f, "arguments to GROUPING must be grouping expressions: {e}")
+            }
+            NestedGroupingSets => write!(f, "ROLLUP, CUBE and GROUPING SETS cannot be nested"),
+            NestedWindow(e) => write!(f, "window function calls cannot be nested: {e}"),
+            WindowInAgg(e) => write!(
+                f,
+                "aggregate function calls cannot contain window function calls: {e}"
+            ),
+            WindowInWhere => write!(f, "WHERE clause cannot contain window functions"),
+            WindowInGroupBy => write!(f, "GROUP BY clause cannot contain window functions"),
+            WindowInHaving => write!(f, "HAVING clause cannot contain window functions"),
+            OrdinalOutOfRange(i) => write!(f, "ORDER BY position {i} is not in select list"),
+            AmbiguousAlias(name) => write!(f, "column reference {name} is ambiguous"),
+            ExpectList(e) => write!(f, "expected a list, found {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 /// Converts the SELECT statement into a plan tree.
 ///
 /// The nodes of all clauses have been added to the `egraph`.
@@ -59,10 +160,30 @@ impl AggExtractor<'_> {
         &self.egraph[id].data.aggs
     }
 
+    fn windows(&self, id: Id) -> &[Expr] {
+        &self.egraph[id].data.windows
+    }
+
     fn node(&self, id: Id) -> &Expr {
         &self.egraph[id].nodes[0]
     }
 
+    /// Returns the elements of the list `id`, which is a clause or a grouping set.
+    ///
+    /// The clauses are not [validated](crate::validate::validate) before planning,
+    /// so a malformed one is reported as an error instead of a panic.
+    fn list(&self, id: Id) -> Result<Vec<Id>, Error> {
+        match self.node(id) {
+            Expr::List(list) => Ok(list.to_vec()),
+            node => Err(Error::ExpectList(self.render(node))),
+        }
+    }
+
+    /// Returns the string of the expression rooted at `expr`.
+    fn render(&self, expr: &Expr) -> String {
+        expr.build_recexpr(|id| self.node(id).clone()).to_string()
+    }
+
     fn plan_select(
         &mut self,
         from: Id,
@@ -72,28 +193,151 @@ impl AggExtractor<'_> {
         orderby: Id,
         projection: Id,
     ) -> Result<Id, Error> {
+        let [projection, having, orderby] =
+            self.resolve_references(from, projection, having, orderby)?;
         if !self.aggs(where_).is_empty() {
             return Err(Error::AggInWhere);
         }
         if !self.aggs(groupby).is_empty() {
             return Err(Error::AggInGroupBy);
         }
+        if !self.windows(where_).is_empty() {
+            return Err(Error::WindowInWhere);
+        }
+        if !self.windows(groupby).is_empty() {
+            return Err(Error::WindowInGroupBy);
+        }
+        if !self.windows(having).is_empty() {
+            return Err(Error::WindowInHaving);
+        }
         let mut plan = self.egraph.add(Expr::Filter([where_, from]));
         let mut to_rewrite = [projection, having, orderby];
         plan = self.plan_agg(&mut to_rewrite, groupby, plan)?;
         let [projection, having, orderby] = to_rewrite;
         plan = self.egraph.add(Expr::Filter([having, plan]));
+        let mut to_rewrite = [projection, orderby];
+        plan = self.plan_window(&mut to_rewrite, plan)?;
+        let [projection, orderby] = to_rewrite;
         plan = self.egraph.add(Expr::Order([orderby, plan]));
         plan = self.egraph.add(Expr::Proj([projection, plan]));
         Ok(plan)
     }
 
+    /// Resolves the references to the select list in `HAVING` and `ORDER BY`,
+    /// and removes the aliases from the select list.
+    ///
+    /// - An alias can be used in `HAVING` if it is not a column of `from`.
+    /// - An order key can be an alias, which takes precedence over the columns of `from`,
+    ///   or the 1-based position of an expression in the select list.
+    ///
+    /// Order keys can also be columns that are not in the select list,
+    /// since the order is planned below the projection.
+    ///
+    /// Returns the new projection, having and orderby.
+    ///
+    /// # Example
+    /// ```text
+    /// select:     (list a (as (sum b) total))
+    /// having:     (> total 1)
+    /// orderby:    (list (desc 2) c)
+    /// output:     (list a (sum b)), (> (sum b) 1), (list (desc (sum b)) c)
+    /// ```
+    fn resolve_references(
+        &mut self,
+        from: Id,
+        projection: Id,
+        having: Id,
+        orderby: Id,
+    ) -> Result<[Id; 3], Error> {
+        use Expr::*;
+        let mut exprs = vec![];
+        let mut aliases = vec![];
+        for item in self.list(projection)? {
+            match self.node(item) {
+                Alias([expr, name]) => {
+                    aliases.push((*name, *expr));
+                    exprs.push(*expr);
+                }
+                _ => exprs.push(item),
+            }
+        }
+        let projection = match aliases.is_empty() {
+            true => projection,
+            false => self.egraph.add(List(exprs.clone().into())),
+        };
+        let columns = self.egraph[from].data.schema.clone().unwrap_or_default();
+        let having = self.resolve_alias_in_expr(having, &aliases, &columns)?;
+        let mut keys = vec![];
+        for key in self.list(orderby)? {
+            let mut node = self.node(key).clone();
+            let key = match &mut node {
+                Asc(k) | Desc(k) => {
+                    *k = self.resolve_order_key(*k, &exprs, &aliases)?;
+                    self.egraph.add(node)
+                }
+                _ => self.resolve_order_key(key, &exprs, &aliases)?,
+            };
+            keys.push(key);
+        }
+        let orderby = self.egraph.add(List(keys.into()));
+        Ok([projection, having, orderby])
+    }
+
+    /// Resolves an order key which is an alias or a position in the select list `exprs`.
+    fn resolve_order_key(&self, key: Id, exprs: &[Id], aliases: &[(Id, Id)]) -> Result<Id, Error> {
+        match self.node(key) {
+            Expr::Constant(Value::Int(i)) => (usize::try_from(*i).ok())
+                .and_then(|i| i.checked_sub(1))
+                .and_then(|i| exprs.get(i).cloned())
+                .ok_or(Error::OrdinalOutOfRange(*i)),
+            Expr::Column(_) => Ok(self.find_alias(key, aliases)?.unwrap_or(key)),
+            _ => Ok(key),
+        }
+    }
+
+    /// Rewrites the expression `id` with aliases that are not in `columns`
+    /// replaced by their expressions. Returns the new expression.
+    fn resolve_alias_in_expr(
+        &mut self,
+        id: Id,
+        aliases: &[(Id, Id)],
+        columns: &[Id],
+    ) -> Result<Id, Error> {
+        let mut expr = self.node(id).clone();
+        if let Expr::Column(_) = expr {
+            if !columns.contains(&id) {
+                return Ok(self.find_alias(id, aliases)?.unwrap_or(id));
+            }
+            return Ok(id);
+        }
+        for child in expr.children_mut() {
+            *child = self.resolve_alias_in_expr(*child, aliases, columns)?;
+        }
+        Ok(self.egraph.add(expr))
+    }
+
+    /// Returns the expression with alias `name`, or `None` if there is no such alias.
+    fn find_alias(&self, name: Id, aliases: &[(Id, Id)]) -> Result<Option<Id>, Error> {
+        let mut found = None;
+        for (alias, expr) in aliases {
+            if *alias != name {
+                continue;
+            }
+            if found.is_some_and(|e| e != *expr) {
+                return Err(Error::AmbiguousAlias(self.render(self.node(name))));
+            }
+            found = Some(*expr);
+        }
+        Ok(found)
+    }
+
     /// Extracts all aggregations from `exprs` and generates an [`Agg`](Expr::Agg) plan.
     /// If no aggregation is found and no `groupby` keys, returns the original `plan`.
     fn plan_agg(&mut self, exprs: &mut [Id], groupby: Id, plan: Id) -> Result<Id, Error> {
         let expr_list = self.egraph.add(Expr::List(exprs.to_vec().into()));
         let aggs = self.aggs(expr_list).to_vec();
-        if aggs.is_empty() && self.node(groupby).as_list().is_empty() {
+        let (groupby, group_keys) = self.plan_grouping_sets(groupby)?;
+        if aggs.is_empty() && group_keys.is_empty() {
             return Ok(plan);
         }
         // check nested agg
@@ -103,26 +347,350 @@ impl AggExtractor<'_> {
                 .iter()
                 .any(|child| !self.aggs(*child).is_empty())
             {
-                return Err(Error::NestedAgg(agg.to_string()));
+                return Err(Error::NestedAgg(self.render(agg)));
+            }
+            if agg
+                .children()
+                .iter()
+                .any(|child| !self.windows(*child).is_empty())
+            {
+                return Err(Error::WindowInAgg(self.render(agg)));
+            }
+            if let Expr::Grouping(key) = agg {
+                if !group_keys.contains(key) {
+                    return Err(Error::GroupingNotInGroupBy(self.render(self.node(*key))));
+                }
             }
         }
+        let (groupby, group_keys, determined) =
+            self.plan_determined_keys(exprs, &aggs, groupby, group_keys, plan);
         let mut list: Vec<_> = aggs.into_iter().map(|agg| self.egraph.add(agg)).collect();
+        let maxs: Vec<_> = (determined.iter())
+            .map(|id| self.egraph.add(Expr::Max(*id)))
+            .collect();
+        list.extend_from_slice(&maxs);
         // make sure the order of the aggs is deterministic
         list.sort();
         list.dedup();
-        let mut schema = list.clone();
-        schema.extend_from_slice(self.node(groupby).as_list());
-        let aggs = self.egraph.add(Expr::List(list.into()));
-        let plan = self.egraph.add(Expr::Agg([aggs, groupby, plan]));
+        let (plan, mut outputs) = match self.plan_distinct_agg(&list, &group_keys, groupby, plan) {
+            Some(planned) => planned,
+            None => {
+                let aggs = self.egraph.add(Expr::List(list.clone().into()));
+                let plan = self.egraph.add(Expr::Agg([aggs, groupby, plan]));
+                (plan, list.clone())
+            }
+        };
+        let mut schema = list;
+        schema.extend_from_slice(&group_keys);
+        outputs.extend_from_slice(&group_keys);
+        // determined expressions are computed by `max`
+        for (id, max) in determined.into_iter().zip(maxs) {
+            let i = schema.iter().position(|x| *x == max).unwrap();
+            schema.push(id);
+            outputs.push(outputs[i]);
+        }
         // check for not aggregated columns
         // rewrite the expressions with a wrapper over agg or group keys
         for id in exprs {
-            *id = self.rewrite_agg_in_expr(*id, &schema)?;
+            *id = self.rewrite_agg_in_expr(*id, &schema, &outputs)?;
         }
         Ok(plan)
     }
 
+    /// Finds the expressions that are functionally determined by the group keys of `plan`.
+    /// They include the group keys determined by other ones, and the columns outside
+    /// aggregations that are not group keys.
+    ///
+    /// Returns the new group keys without the determined ones, all keys in it,
+    /// and the determined expressions, which can be computed by any aggregation over
+    /// the same values, e.g. `max`.
+    ///
+    /// # Example
+    /// ```text
+    /// select:     t.id, t.name, count(*)
+    /// groupby:    t.id, t.name
+    /// output:     (list t.id)
+    /// determined: t.name              (t.id is the primary key of t)
+    /// ```
+    fn plan_determined_keys(
+        &mut self,
+        exprs: &[Id],
+        aggs: &[Expr],
+        groupby: Id,
+        mut group_keys: Vec<Id>,
+        plan: Id,
+    ) -> (Id, Vec<Id>, Vec<Id>) {
+        // no functional dependency across grouping sets
+        if !matches!(self.node(groupby), Expr::List(_)) {
+            return (groupby, group_keys, vec![]);
+        }
+        let fds = &self.egraph[plan].data.fds;
+        let mut determined = vec![];
+        for i in (0..group_keys.len()).rev() {
+            let mut others = group_keys.clone();
+            let key = others.remove(i);
+            // keep at least one key, otherwise there is always one group
+            if !others.is_empty() && fds.closure(&others).contains(&key) {
+                group_keys = others;
+                determined.insert(0, key);
+            }
+        }
+        let closure = fds.closure(&group_keys);
+        let mut columns = vec![];
+        for id in exprs {
+            self.columns_outside_aggs(*id, aggs, &mut columns);
+        }
+        for column in columns {
+            if !group_keys.contains(&column)
+                && !determined.contains(&column)
+                && closure.contains(&column)
+            {
+                determined.push(column);
+            }
+        }
+        if determined.is_empty() {
+            return (groupby, group_keys, determined);
+        }
+        let groupby = self.egraph.add(Expr::List(group_keys.clone().into()));
+        (groupby, group_keys, determined)
+    }
+
+    /// Appends the columns in the expression `id` that are not in any of `aggs` to `columns`.
+    fn columns_outside_aggs(&self, id: Id, aggs: &[Expr], columns: &mut Vec<Id>) {
+        let expr = self.node(id);
+        if aggs.contains(expr) {
+            return;
+        }
+        if let Expr::Column(_) = expr {
+            if !columns.contains(&id) {
+                columns.push(id);
+            }
+            return;
+        }
+        for child in expr.children() {
+            self.columns_outside_aggs(*child, aggs, columns);
+        }
+    }
+
+    /// Expands `ROLLUP`, `CUBE` and `GROUPING SETS` in the group keys into a
+    /// [`GroupingSets`](Expr::GroupingSets) node. Each item of the group keys gives a list
+    /// of grouping sets, and the result is the cross product of them.
+    ///
+    /// Returns the new group keys and all keys in it.
+    /// If there is no grouping set, returns the original `groupby`.
+    ///
+    /// # Example
+    /// ```text
+    /// groupby:    (list a (rollup (list b c)))
+    /// output:     (grouping-sets (list (list a b c) (list a b) (list a)))
+    /// keys:       a, b, c
+    /// ```
+    fn plan_grouping_sets(&mut self, groupby: Id) -> Result<(Id, Vec<Id>), Error> {
+        use Expr::*;
+        let items = self.list(groupby)?;
+        let mut sets = vec![vec![]];
+        let mut has_grouping_sets = false;
+        for item in items {
+            let choices: Vec<Vec<Id>> = match self.node(item).clone() {
+                Rollup(list) => {
+                    let keys = self.grouping_keys(list)?;
+                    (0..=keys.len()).rev().map(|i| keys[..i].to_vec()).collect()
+                }
+                Cube(list) => {
+                    let keys = self.grouping_keys(list)?;
+                    let n = keys.len();
+                    (0..1usize << n)
+                        .rev()
+                        .map(|mask| {
+                            (keys.iter().enumerate())
+                                .filter(|(i, _)| mask & (1 << (n - 1 - i)) != 0)
+                                .map(|(_, key)| *key)
+                                .collect()
+                        })
+                        .collect()
+                }
+                GroupingSets(list) => (self.list(list)?.into_iter())
+                    .map(|set| match self.node(set) {
+                        List(_) => self.grouping_keys(set),
+                        _ => Err(Error::NestedGroupingSets),
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => vec![vec![item]],
+            };
+            has_grouping_sets |= matches!(self.node(item), Rollup(_) | Cube(_) | GroupingSets(_));
+            sets = (sets.iter())
+                .flat_map(|set| {
+                    choices.iter().map(move |choice| {
+                        let mut set = set.clone();
+                        for key in choice {
+                            if !set.contains(key) {
+                                set.push(*key);
+                            }
+                        }
+                        set
+                    })
+                })
+                .collect();
+        }
+        if !has_grouping_sets {
+            let keys = self.list(groupby)?;
+            return Ok((groupby, keys));
+        }
+        let mut keys = vec![];
+        for key in sets.iter().flatten() {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        let sets = (sets.into_iter())
+            .map(|set| self.egraph.add(List(set.into())))
+            .collect();
+        let sets = self.egraph.add(List(sets));
+        let groupby = self.egraph.add(GroupingSets(sets));
+        Ok((groupby, keys))
+    }
+
+    /// Returns the keys in the list of a grouping set, which must not be a grouping set.
+    fn grouping_keys(&self, list: Id) -> Result<Vec<Id>, Error> {
+        let keys = self.list(list)?;
+        if keys.iter().any(|key| {
+            matches!(
+                self.node(*key),
+                Expr::Rollup(_) | Expr::Cube(_) | Expr::GroupingSets(_)
+            )
+        }) {
+            return Err(Error::NestedGroupingSets);
+        }
+        Ok(keys)
+    }
+
+    /// Generates two levels of [`Agg`](Expr::Agg) plans for aggregations with `DISTINCT`.
+    ///
+    /// The lower one groups by the group keys and the distinct argument to remove duplicates,
+    /// and computes the partial results of other aggregations.
+    /// The upper one aggregates over the distinct values and merges the partial results.
+    ///
+    /// Returns the upper plan and the output of it for each agg in `aggs`.
+    /// Returns `None` if there is no distinct aggregation or the aggregations can not be split.
+    /// Without group keys, `count` is not split since its sum over no rows would be null instead of 0.
+    fn plan_distinct_agg(
+        &mut self,
+        aggs: &[Id],
+        group_keys: &[Id],
+        groupby: Id,
+        plan: Id,
+    ) -> Option<(Id, Vec<Id>)> {
+        use Expr::*;
+        // a distinct value may be counted in multiple groups of the lower agg
+        if !matches!(self.node(groupby), List(_)) {
+            return None;
+        }
+        let mut distinct_arg = None;
+        for agg in aggs {
+            match self.node(*agg) {
+                CountDistinct(a) | SumDistinct(a) => {
+                    if distinct_arg.is_some_and(|arg| arg != *a) {
+                        return None;
+                    }
+                    distinct_arg = Some(*a);
+                }
+                Avg(_) => return None,
+                // the upper agg would sum the counts, which is null instead of 0 on empty input
+                Count(_) | RowCount if group_keys.is_empty() => return None,
+                _ => {}
+            }
+        }
+        let arg = distinct_arg?;
+        let lower_aggs = (aggs.iter().cloned())
+            .filter(|agg| !matches!(self.node(*agg), CountDistinct(_) | SumDistinct(_)))
+            .collect();
+        let lower_aggs = self.egraph.add(List(lower_aggs));
+        let mut lower_keys = group_keys.to_vec();
+        lower_keys.push(arg);
+        let lower_keys = self.egraph.add(List(lower_keys.into()));
+        let lower = self.egraph.add(Agg([lower_aggs, lower_keys, plan]));
+
+        let mut outputs = vec![];
+        for agg in aggs {
+            let nested = self.egraph.add(Nested(*agg));
+            let upper_agg = match self.node(*agg).clone() {
+                CountDistinct(a) => Count(self.egraph.add(Nested(a))),
+                SumDistinct(a) => Sum(self.egraph.add(Nested(a))),
+                Count(_) | RowCount | Sum(_) => Sum(nested),
+                Max(_) => Max(nested),
+                Min(_) => Min(nested),
+                node => panic!("not aggregation: {node}"),
+            };
+            outputs.push(self.egraph.add(upper_agg));
+        }
+        let upper_aggs = self.egraph.add(List(outputs.clone().into()));
+        let upper = self.egraph.add(Agg([upper_aggs, groupby, lower]));
+        Some((upper, outputs))
+    }
+
+    /// Extracts all window functions from `exprs` and generates [`Window`](Expr::Window) plans,
+    /// one for each distinct pair of partition keys and order keys.
+    /// If no window function is found, returns the original `plan`.
+    fn plan_window(&mut self, exprs: &mut [Id], mut plan: Id) -> Result<Id, Error> {
+        let expr_list = self.egraph.add(Expr::List(exprs.to_vec().into()));
+        let windows = self.windows(expr_list).to_vec();
+        if windows.is_empty() {
+            return Ok(plan);
+        }
+        // check nested window
+        for window in windows.iter() {
+            if window
+                .children()
+                .iter()
+                .any(|child| !self.windows(*child).is_empty())
+            {
+                return Err(Error::NestedWindow(self.render(window)));
+            }
+        }
+        let mut list: Vec<_> = (windows.into_iter())
+            .map(|window| self.egraph.add(window))
+            .collect();
+        // make sure the order of the windows is deterministic
+        list.sort();
+        list.dedup();
+        // group the windows by their partition keys and order keys
+        let mut groups: Vec<([Id; 2], Vec<Id>)> = vec![];
+        for id in list.iter() {
+            let Expr::Over([_, partition, order]) = self.node(*id) else {
+                panic!("not window function: {}", self.node(*id));
+            };
+            let spec = [*partition, *order];
+            match groups.iter_mut().find(|(s, _)| *s == spec) {
+                Some((_, group)) => group.push(*id),
+                None => groups.push((spec, vec![*id])),
+            }
+        }
+        for (_, group) in groups {
+            let windows = self.egraph.add(Expr::List(group.into()));
+            plan = self.egraph.add(Expr::Window([windows, plan]));
+        }
+        for id in exprs {
+            *id = self.rewrite_window_in_expr(*id, &list);
+        }
+        Ok(plan)
+    }
+
+    /// Rewrites the expression `id` with window functions in `windows`
+    /// wrapped in a [`Nested`](Expr::Nested) node.
+    /// Returns the new expression.
+    fn rewrite_window_in_expr(&mut self, id: Id, windows: &[Id]) -> Id {
+        if windows.contains(&id) {
+            return self.egraph.add(Expr::Nested(id));
+        }
+        let mut expr = self.node(id).clone();
+        for child in expr.children_mut() {
+            *child = self.rewrite_window_in_expr(*child, windows);
+        }
+        self.egraph.add(expr)
+    }
+
     /// Rewrites the expression `id` with aggs wrapped in a [`Nested`](Expr::Nested) node.
+    /// `outputs[i]` is the output of the plan that computes `schema[i]`.
     /// Returns the new expression.
     ///
     /// # Example
@@ -135,17 +703,17 @@ impl AggExtractor<'_> {
     ///             (+ b (+ (sum a) 1))
     /// which can not be composed by `schema`
     /// ```
-    fn rewrite_agg_in_expr(&mut self, id: Id, schema: &[Id]) -> Result<Id, Error> {
+    fn rewrite_agg_in_expr(&mut self, id: Id, schema: &[Id], outputs: &[Id]) -> Result<Id, Error> {
         let mut expr = self.node(id).clone();
-        if schema.contains(&id) {
+        if let Some(i) = schema.iter().position(|x| *x == id) {
             // found agg, wrap it with Nested
-            return Ok(self.egraph.add(Expr::Nested(id)));
+            return Ok(self.egraph.add(Expr::Nested(outputs[i])));
         }
         if let Expr::Column(cid) = &expr {
             return Err(Error::ColumnNotInAgg(cid.to_string()));
         }
         for child in expr.children_mut() {
-            *child = self.rewrite_agg_in_expr(*child, schema)?;
+            *child = self.rewrite_agg_in_expr(*child, schema, outputs)?;
         }
         Ok(self.egraph.add(expr))
     }
@@ -0,0 +1,303 @@
+//! An interactive shell to try the optimizer.
+//!
+//! It reads plans as S-expressions from a file or stdin, optimizes them
+//! and prints the best plan, its cost and the statistics of each stage.
+//! A plan can span multiple lines. Lines starting with `.` are commands, see `.help`.
+//!
+//! ```text
+//! cargo run --bin repl [FILE]
+//! ```
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+use sql_optimizer_labs::explain::Explain;
+use sql_optimizer_labs::optimizer::RuleGroup;
+use sql_optimizer_labs::{ExprAnalysis, Optimizer, RecExpr};
+
+const HELP: &str = "\
+Enter a plan as an S-expression, e.g. (filter (= a 1) (scan t (list a b)))
+
+.rules                        list the rule groups
+.rules enable|disable GROUP.. enable or disable rule groups
+.rules only GROUP..           enable only the rule groups
+.rules all                    enable all rule groups
+.explain on|off               print the plan as an operator tree
+.trace on|off                 print the rules applied to the plan
+.catalog                      list the primary keys
+.catalog load FILE            load primary keys from lines of `TABLE COLUMN..`
+.catalog clear                remove all primary keys
+.limits                       show the limits of each stage
+.limits node|iter N           set the maximum number of enodes or iterations
+.limits time SECONDS          set the maximum time
+.help                         show this message
+.quit                         exit
+";
+
+/// The state of the shell.
+struct Repl {
+    rule_groups: Vec<RuleGroup>,
+    analysis: ExprAnalysis,
+    node_limit: usize,
+    iter_limit: usize,
+    time_limit: Duration,
+    explain: bool,
+    trace: bool,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl {
+            rule_groups: RuleGroup::ALL.to_vec(),
+            analysis: ExprAnalysis::default(),
+            node_limit: 10_000,
+            iter_limit: 30,
+            time_limit: Duration::from_secs(5),
+            explain: false,
+            trace: false,
+        }
+    }
+}
+
+fn main() {
+    let input: Box<dyn BufRead> = match std::env::args().nth(1) {
+        Some(path) => match std::fs::File::open(&path) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(e) => {
+                eprintln!("failed to open {path}: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(io::stdin().lock()),
+    };
+    let interactive = std::env::args().nth(1).is_none() && io::stdin().is_terminal();
+    let mut repl = Repl::default();
+    let mut plan = String::new();
+    let mut lines = input.lines();
+    loop {
+        if interactive {
+            print!("{}", if plan.is_empty() { "> " } else { "... " });
+            io::stdout().flush().unwrap();
+        }
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        if plan.is_empty() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("--") {
+                continue;
+            }
+            if let Some(command) = line.strip_prefix('.') {
+                if !repl.command(command) {
+                    break;
+                }
+                continue;
+            }
+            if !line.starts_with('(') {
+                println!("error: SQL is not supported, enter a plan as an S-expression");
+                continue;
+            }
+        }
+        plan.push_str(&line);
+        plan.push('\n');
+        if depth(&plan) <= 0 {
+            repl.optimize(&std::mem::take(&mut plan));
+        }
+    }
+    if !plan.trim().is_empty() {
+        repl.optimize(&plan);
+    }
+}
+
+impl Repl {
+    /// Runs a command. Returns false if the shell should exit.
+    fn command(&mut self, command: &str) -> bool {
+        let mut args = command.split_whitespace();
+        let result = match args.next().unwrap_or_default() {
+            "help" => {
+                print!("{HELP}");
+                Ok(())
+            }
+            "quit" | "exit" => return false,
+            "rules" => self.rules(args.collect()),
+            "explain" => on_off(args.next(), &mut self.explain),
+            "trace" => on_off(args.next(), &mut self.trace),
+            "catalog" => self.catalog(args.collect()),
+            "limits" => self.limits(args.collect()),
+            name => Err(format!("unknown command .{name}, see .help")),
+        };
+        if let Err(e) = result {
+            println!("error: {e}");
+        }
+        true
+    }
+
+    fn rules(&mut self, args: Vec<&str>) -> Result<(), String> {
+        let groups = || -> Result<Vec<_>, _> { args[1..].iter().map(|s| parse_group(s)).collect() };
+        match args.first() {
+            None => {
+                for group in RuleGroup::ALL {
+                    let enabled = self.rule_groups.contains(&group);
+                    println!(
+                        "[{}] {} ({} rules)",
+                        if enabled { "x" } else { " " },
+                        group_name(group),
+                        group.rules().len()
+                    );
+                }
+            }
+            Some(&"all") => self.rule_groups = RuleGroup::ALL.to_vec(),
+            Some(&"only") => self.rule_groups = groups()?,
+            Some(&"enable") => {
+                for group in groups()? {
+                    if !self.rule_groups.contains(&group) {
+                        self.rule_groups.push(group);
+                    }
+                }
+            }
+            Some(&"disable") => {
+                let groups = groups()?;
+                self.rule_groups.retain(|g| !groups.contains(g));
+            }
+            Some(arg) => return Err(format!("unknown argument {arg}, see .help")),
+        }
+        Ok(())
+    }
+
+    fn catalog(&mut self, args: Vec<&str>) -> Result<(), String> {
+        match args[..] {
+            [] => {
+                let mut tables: Vec<_> = self.analysis.primary_keys.iter().collect();
+                tables.sort_by_key(|(table, _)| table.as_str());
+                for (table, key) in tables {
+                    let key: Vec<_> = key.iter().map(|c| c.as_str()).collect();
+                    println!("{table}: primary key ({})", key.join(", "));
+                }
+            }
+            ["load", path] => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("failed to read {path}: {e}"))?;
+                for line in text.lines() {
+                    let mut words = line.split_whitespace();
+                    let Some(table) = words.next().filter(|w| !w.starts_with('#')) else {
+                        continue;
+                    };
+                    let key: Vec<_> = words.map(|c| c.into()).collect();
+                    if key.is_empty() {
+                        return Err(format!("no primary key for table {table}"));
+                    }
+                    self.analysis.primary_keys.insert(table.into(), key);
+                }
+            }
+            ["clear"] => self.analysis.primary_keys.clear(),
+            _ => return Err("invalid arguments, see .help".into()),
+        }
+        Ok(())
+    }
+
+    fn limits(&mut self, args: Vec<&str>) -> Result<(), String> {
+        let number = |s: &str| s.parse().map_err(|_| format!("invalid number {s}"));
+        match args[..] {
+            [] => {
+                println!("node: {}", self.node_limit);
+                println!("iter: {}", self.iter_limit);
+                println!("time: {}s", self.time_limit.as_secs_f32());
+            }
+            ["node", n] => self.node_limit = number(n)?,
+            ["iter", n] => self.iter_limit = number(n)?,
+            ["time", s] => {
+                let secs = s.parse().map_err(|_| format!("invalid number {s}"))?;
+                self.time_limit = Duration::try_from_secs_f32(secs)
+                    .map_err(|_| format!("invalid time {s}, expect a non-negative number"))?;
+            }
+            _ => return Err("invalid arguments, see .help".into()),
+        }
+        Ok(())
+    }
+
+    /// Optimizes the plan and prints the result.
+    fn optimize(&self, plan: &str) {
+        let expr: RecExpr = match plan.parse() {
+            Ok(expr) => expr,
+            Err(e) => return println!("error: {e}"),
+        };
+        let mut optimizer = Optimizer::new()
+            .rule_groups(&self.rule_groups)
+            .analysis(self.analysis.clone())
+            .node_limit(self.node_limit)
+            .iter_limit(self.iter_limit)
+            .time_limit(self.time_limit);
+        if self.trace {
+            optimizer = optimizer.with_trace();
+        }
+        let (best, report) = match optimizer.optimize(&expr) {
+            Ok(result) => result,
+            Err(e) => return println!("error: {e}"),
+        };
+        if self.trace {
+            print!("{}", report.trace_log());
+        }
+        if self.explain {
+            let explain = Explain::new(&best)
+                .with_rows()
+                .with_cost()
+                .analysis(self.analysis.clone());
+            print!("{explain}");
+        } else {
+            println!("{}", best.pretty(80));
+        }
+        println!("cost: {} -> {}", report.cost_before, report.cost_after);
+        for stage in report.stages.iter() {
+            println!(
+                "stage {}: {} iterations, {} nodes, {} classes, {:?}",
+                stage.name,
+                stage.iterations,
+                stage.egraph_nodes,
+                stage.egraph_classes,
+                stage.stop_reason
+            );
+        }
+    }
+}
+
+/// Returns the number of unclosed parentheses outside of string literals.
+fn depth(plan: &str) -> i32 {
+    let mut depth = 0;
+    let mut quoted = false;
+    for c in plan.chars() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn on_off(arg: Option<&str>, flag: &mut bool) -> Result<(), String> {
+    match arg {
+        Some("on") => *flag = true,
+        Some("off") => *flag = false,
+        _ => return Err("expected on or off".into()),
+    }
+    Ok(())
+}
+
+/// Returns the name of a rule group in kebab case, e.g. `predicate-pushdown`.
+fn group_name(group: RuleGroup) -> String {
+    let mut name = String::new();
+    for c in format!("{group:?}").chars() {
+        if c.is_uppercase() && !name.is_empty() {
+            name.push('-');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+fn parse_group(name: &str) -> Result<RuleGroup, String> {
+    (RuleGroup::ALL.into_iter())
+        .find(|g| group_name(*g) == name)
+        .ok_or_else(|| format!("unknown rule group {name}, see .rules"))
+}
@@ -0,0 +1,70 @@
+//! Run test scripts of plans. See [`sql_optimizer_labs::slt`] for the format.
+//!
+//! ```text
+//! cargo run --bin slt [--override] PATH..
+//! ```
+//!
+//! A path can be a script or a directory of `*.slt` scripts.
+//! With `--override`, the expected output in the scripts is replaced by the actual output.
+
+use std::path::PathBuf;
+
+use sql_optimizer_labs::slt;
+
+fn main() {
+    let mut override_ = false;
+    let mut paths = vec![];
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--override" => override_ = true,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("usage: slt [--override] PATH..");
+        std::process::exit(2);
+    }
+    let mut failed = false;
+    for path in scripts(&paths) {
+        let script = match std::fs::read_to_string(&path) {
+            Ok(script) => script,
+            Err(e) => {
+                eprintln!("failed to read {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        };
+        let outcome = slt::run(&script);
+        if override_ {
+            if outcome.output != script {
+                std::fs::write(&path, &outcome.output).unwrap();
+                println!("updated {}", path.display());
+            }
+            continue;
+        }
+        for failure in outcome.failures.iter() {
+            println!("{}:{}", path.display(), failure);
+        }
+        failed |= !outcome.failures.is_empty();
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Returns the scripts in the paths, sorted by name in each directory.
+fn scripts(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut scripts = vec![];
+    for path in paths {
+        if !path.is_dir() {
+            scripts.push(path.clone());
+            continue;
+        }
+        let mut files: Vec<_> = (std::fs::read_dir(path).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|p| p.extension() == Some("slt".as_ref()))
+            .collect();
+        files.sort();
+        scripts.extend(files);
+    }
+    scripts
+}
@@ -0,0 +1,148 @@
+//! Estimate the cardinality of plan nodes and the cost of plans.
+
+use egg::{CostFunction, Language};
+
+use super::*;
+
+/// The number of rows assumed for a table without statistics.
+pub const DEFAULT_TABLE_ROWS: f32 = 1000.0;
+
+/// The fraction of rows assumed to pass a non-constant predicate.
+pub const DEFAULT_SELECTIVITY: f32 = 0.1;
+
+/// The data type of cardinality analysis.
+///
+/// It is always 0 for non-plan nodes.
+pub type Rows = f32;
+
+/// Returns the estimated number of output rows of the node.
+pub fn analyze_rows(egraph: &EGraph, enode: &Expr) -> Rows {
+    use Expr::*;
+    let x = |i: &Id| egraph[*i].data.rows;
+    let get_limit = |i: &Id| match &egraph[*i].data.constant {
+        Some(Value::Int(n)) => Some(*n as f32),
+        _ => None,
+    };
+    match enode {
+        Scan(_) => DEFAULT_TABLE_ROWS,
+        Values(vs) => vs.len() as f32,
+        Proj([_, c]) | Order([_, c]) | Window([_, c]) => x(c),
+        Filter([cond, c]) => x(c) * selectivity(egraph, *cond),
+        Limit([limit, _, c]) | TopN([limit, _, _, c]) => match get_limit(limit) {
+            Some(limit) => x(c).min(limit),
+            None => x(c),
+        },
+        Join([ty, cond, l, r]) | NestedLoopJoin([ty, cond, l, r]) => {
+            let rows = x(l) * x(r) * selectivity(egraph, *cond);
+            outer_join_rows(egraph, *ty, x(l), x(r), rows)
+        }
+        HashJoin([ty, _, _, l, r]) | MergeJoin([ty, _, _, l, r]) => {
+            let rows = x(l) * x(r) * DEFAULT_SELECTIVITY;
+            outer_join_rows(egraph, *ty, x(l), x(r), rows)
+        }
+        Agg([_, group_keys, c]) | FinalAgg([_, group_keys, c]) => {
+            match &egraph[*group_keys].data.schema {
+                Some(keys) if keys.is_empty() => 1.0,
+                _ => (x(c) * DEFAULT_SELECTIVITY).max(1.0),
+            }
+        }
+        Distinct(c) => x(c),
+        Union([l, r]) | UnionAll([l, r]) => x(l) + x(r),
+        Intersect([l, r]) => x(l).min(x(r)),
+        Except([l, _]) => x(l),
+        Empty(_) => 0.0,
+        // not plan node
+        _ => 0.0,
+    }
+}
+
+/// Returns the estimated fraction of rows that satisfy the predicate.
+fn selectivity(egraph: &EGraph, cond: Id) -> f32 {
+    match &egraph[cond].data.constant {
+        Some(Value::Bool(true)) => 1.0,
+        Some(_) => 0.0,
+        None => DEFAULT_SELECTIVITY,
+    }
+}
+
+/// Adjusts the rows of an inner join for outer joins,
+/// which produce at least one row for each row of the preserved side.
+fn outer_join_rows(egraph: &EGraph, ty: Id, left: Rows, right: Rows, rows: Rows) -> Rows {
+    let is = |e: Expr| egraph[ty].nodes.contains(&e);
+    if is(Expr::LeftOuter) {
+        rows.max(left)
+    } else if is(Expr::RightOuter) {
+        rows.max(right)
+    } else if is(Expr::FullOuter) {
+        rows.max(left + right)
+    } else {
+        rows
+    }
+}
+
+/// Merge two estimations and keep the smaller one.
+pub fn merge(to: &mut Rows, from: Rows) -> DidMerge {
+    if from < *to {
+        *to = from;
+        DidMerge(true, false)
+    } else {
+        DidMerge(false, *to < from)
+    }
+}
+
+/// The cost function for plan extraction.
+///
+/// Expressions cost 1 per node and are evaluated once per input row.
+/// Plan nodes cost the rows they process.
+pub struct CostFn<'a> {
+    pub egraph: &'a EGraph,
+}
+
+impl CostFunction<Expr> for CostFn<'_> {
+    type Cost = f32;
+
+    fn cost<C>(&mut self, enode: &Expr, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(Id) -> Self::Cost,
+    {
+        use Expr::*;
+        let rows = |i: &Id| self.egraph[*i].data.rows;
+        let mut c = |i: &Id| costs(*i);
+        match enode {
+            Scan([_, columns]) => DEFAULT_TABLE_ROWS * c(columns),
+            Values(vs) => vs.iter().map(c).sum(),
+            Proj([exprs, child]) | Filter([exprs, child]) => c(child) + rows(child) * c(exprs),
+            Order([keys, child]) => c(child) + sort_cost(rows(child)) * c(keys),
+            Limit([_, _, child]) => c(child) + rows(child),
+            TopN([_, _, keys, child]) => c(child) + rows(child) * c(keys),
+            NestedLoopJoin([_, cond, l, r]) => c(l) + c(r) + rows(l) * rows(r) * c(cond),
+            // a logical join is executed as a nested loop join if not converted,
+            // but prefer the physical one
+            Join([_, cond, l, r]) => c(l) + c(r) + rows(l) * rows(r) * c(cond) + 1.0,
+            // the right side is used to build the hash table
+            HashJoin([_, lkeys, rkeys, l, r]) => {
+                c(l) + c(r) + rows(l) * c(lkeys) + 2.0 * rows(r) * c(rkeys)
+            }
+            // both sides are scanned once, but they must be sorted
+            MergeJoin([_, lkeys, rkeys, l, r]) => {
+                c(l) + c(r) + rows(l) * c(lkeys) + rows(r) * c(rkeys)
+            }
+            Agg([aggs, group_keys, child]) | FinalAgg([aggs, group_keys, child]) => {
+                c(child) + rows(child) * (c(aggs) + c(group_keys))
+            }
+            // rows are sorted by the partition and order keys
+            Window([exprs, child]) => c(child) + sort_cost(rows(child)) * c(exprs),
+            Distinct(child) => c(child) + rows(child),
+            UnionAll([l, r]) => c(l) + c(r),
+            Union([l, r]) | Intersect([l, r]) | Except([l, r]) => c(l) + c(r) + rows(l) + rows(r),
+            Empty(_) => 1.0,
+            // expressions
+            _ => enode.fold(1.0, |sum, id| sum + c(&id)),
+        }
+    }
+}
+
+/// Returns the relative cost of sorting `rows` rows.
+fn sort_cost(rows: Rows) -> f32 {
+    rows * rows.max(2.0).log2()
+}
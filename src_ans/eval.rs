@@ -0,0 +1,615 @@
+//! Execute a plan on in-memory tables.
+//!
+//! The evaluator is a reference to check that an optimized plan returns the same rows
+//! as its input plan. It is simple rather than fast: every operator materializes its
+//! output, and all joins are nested loop joins.
+//!
+//! Expressions are resolved as in [`schema`]: an expression in the output of the child
+//! takes the value of that column, e.g. `(sum a)` above an aggregation.
+
+use std::cmp::Ordering;
+
+use egg::Language;
+
+use super::*;
+
+/// A row of values.
+pub type Row = Vec<Value>;
+
+/// An in-memory table.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Table {
+    /// The column names, which are matched with the columns of a scan
+    /// with or without the prefix of the table name or its alias, e.g. `t.a`, `t1.a` or `a`.
+    pub columns: Vec<Column>,
+    pub rows: Vec<Row>,
+}
+
+/// The values of the order keys and whether they are descending.
+type SortKey = Vec<(Value, bool)>;
+
+/// The tables by name.
+pub type Tables = HashMap<Column, Table>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    // #[error("table {0} not found")]
+    TableNotFound(String),
+    // #[error("column {0} not found")]
+    ColumnNotFound(String),
+    // #[error("division by zero")]
+    DivisionByZero,
+    // #[error("integer out of range: {0}")]
+    Overflow(String),
+    // #[error("invalid operand types: {0}")]
+    TypeMismatch(String),
+    // #[error("can not evaluate {0}")]
+    Unsupported(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::TableNotFound(t) => write!(f, "table {t} not found"),
+            Error::ColumnNotFound(c) => write!(f, "column {c} not found"),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow(e) => write!(f, "integer out of range: {e}"),
+            Error::TypeMismatch(e) => write!(f, "invalid operand types: {e}"),
+            Error::Unsupported(e) => write!(f, "can not evaluate {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Executes the plan on the tables and returns the output rows.
+pub fn execute(tables: &Tables, plan: &RecExpr) -> Result<Vec<Row>, Error> {
+    if plan.as_ref().is_empty() {
+        return Err(Error::Unsupported(plan.to_string()));
+    }
+    // add the plan to an egraph to get the schema of each node
+    let mut egraph = EGraph::default();
+    let mut ids: Vec<Id> = Vec::with_capacity(plan.as_ref().len());
+    for node in plan.as_ref() {
+        let node = node.clone().map_children(|i| ids[usize::from(i)]);
+        ids.push(egraph.add(node));
+    }
+    let executor = Executor {
+        tables,
+        expr: plan,
+        egraph,
+        ids,
+    };
+    executor.plan(Id::from(plan.as_ref().len() - 1))
+}
+
+struct Executor<'a> {
+    tables: &'a Tables,
+    expr: &'a RecExpr,
+    egraph: EGraph,
+    /// The eclass of each node in the plan.
+    ids: Vec<Id>,
+}
+
+impl Executor<'_> {
+    fn node(&self, id: Id) -> &Expr {
+        &self.expr[id]
+    }
+
+    fn eclass(&self, id: Id) -> Id {
+        self.egraph.find(self.ids[usize::from(id)])
+    }
+
+    /// Returns the output expressions of the plan, which are eclasses in the egraph.
+    fn schema(&self, id: Id) -> Vec<Id> {
+        let schema = self.egraph[self.ids[usize::from(id)]].data.schema.clone();
+        (schema.unwrap_or_default().into_iter())
+            .map(|id| self.egraph.find(id))
+            .collect()
+    }
+
+    /// Returns the elements of the list.
+    fn list(&self, id: Id) -> &[Id] {
+        match self.node(id) {
+            Expr::List(list) => list,
+            _ => &[],
+        }
+    }
+
+    fn render(&self, id: Id) -> String {
+        self.node(id)
+            .build_recexpr(|id| self.node(id).clone())
+            .to_string()
+    }
+
+    fn unsupported(&self, id: Id) -> Error {
+        Error::Unsupported(self.render(id))
+    }
+
+    fn overflow(&self, id: Id) -> Error {
+        Error::Overflow(self.render(id))
+    }
+
+    fn type_mismatch(&self, id: Id) -> Error {
+        Error::TypeMismatch(self.render(id))
+    }
+
+    /// Returns the output rows of the plan.
+    fn plan(&self, id: Id) -> Result<Vec<Row>, Error> {
+        use Expr::*;
+        Ok(match self.node(id) {
+            Scan([table, columns]) => self.scan(*table, *columns)?,
+            Values(rows) => (rows.iter())
+                .map(|row| self.eval_list(*row, &[], &vec![]))
+                .collect::<Result<_, _>>()?,
+            Proj([exprs, c]) => {
+                let input = self.schema(*c);
+                (self.plan(*c)?.iter())
+                    .map(|row| self.eval_list(*exprs, &input, row))
+                    .collect::<Result<_, _>>()?
+            }
+            Filter([cond, c]) => {
+                let input = self.schema(*c);
+                let mut rows = vec![];
+                for row in self.plan(*c)? {
+                    if self.eval(*cond, &input, &row)? == Value::Bool(true) {
+                        rows.push(row);
+                    }
+                }
+                rows
+            }
+            Order([keys, c]) => self.order(*keys, *c)?,
+            Limit([limit, offset, c]) => self.limit(*limit, *offset, self.plan(*c)?)?,
+            TopN([limit, offset, keys, c]) => {
+                self.limit(*limit, *offset, self.order(*keys, *c)?)?
+            }
+            Join([ty, cond, l, r]) | NestedLoopJoin([ty, cond, l, r]) => {
+                let input = [self.schema(*l), self.schema(*r)].concat();
+                self.join(*ty, *l, *r, |lrow, rrow| {
+                    let row = [lrow.as_slice(), rrow].concat();
+                    Ok(self.eval(*cond, &input, &row)? == Value::Bool(true))
+                })?
+            }
+            HashJoin([ty, lkeys, rkeys, l, r]) | MergeJoin([ty, lkeys, rkeys, l, r]) => {
+                let (linput, rinput) = (self.schema(*l), self.schema(*r));
+                self.join(*ty, *l, *r, |lrow, rrow| {
+                    let lkeys = self.eval_list(*lkeys, &linput, lrow)?;
+                    let rkeys = self.eval_list(*rkeys, &rinput, rrow)?;
+                    Ok(lkeys.iter().all(|v| !v.is_null()) && lkeys == rkeys)
+                })?
+            }
+            Agg([aggs, keys, c]) => {
+                let input = self.schema(*c);
+                self.group(*aggs, *keys, *c, |agg, set, rows| match self.node(agg) {
+                    Grouping(key) => Ok(Value::Int(!set.contains(&self.eclass(*key)) as i32)),
+                    _ => self.aggregate(agg, &input, rows),
+                })?
+            }
+            FinalAgg([aggs, keys, c]) => {
+                let input = self.schema(*c);
+                self.group(*aggs, *keys, *c, |agg, _, rows| {
+                    self.final_aggregate(agg, &input, rows)
+                })?
+            }
+            Window([overs, c]) => self.window(*overs, *c)?,
+            Distinct(c) => distinct(self.plan(*c)?),
+            Union([l, r]) => distinct([self.plan(*l)?, self.plan(*r)?].concat()),
+            UnionAll([l, r]) => [self.plan(*l)?, self.plan(*r)?].concat(),
+            Intersect([l, r]) => {
+                let right = self.plan(*r)?;
+                distinct(
+                    self.plan(*l)?
+                        .into_iter()
+                        .filter(|row| right.contains(row))
+                        .collect(),
+                )
+            }
+            Except([l, r]) => {
+                let right = self.plan(*r)?;
+                distinct(
+                    self.plan(*l)?
+                        .into_iter()
+                        .filter(|row| !right.contains(row))
+                        .collect(),
+                )
+            }
+            Empty(_) => vec![],
+            _ => return Err(self.unsupported(id)),
+        })
+    }
+
+    fn scan(&self, table: Id, columns: Id) -> Result<Vec<Row>, Error> {
+        let name = self.render(table);
+        let table = (self.tables.get(&name.as_str().into()))
+            .ok_or_else(|| Error::TableNotFound(name.clone()))?;
+        // the column name without the prefix of the table or its alias
+        let base = |c: &str| c.rsplit_once('.').map_or(c, |(_, c)| c).to_string();
+        let mut indices = vec![];
+        for column in self.list(columns) {
+            let column = self.render(*column);
+            let index = (table.columns.iter())
+                .position(|c| base(c.as_str()) == base(&column))
+                .ok_or(Error::ColumnNotFound(column))?;
+            indices.push(index);
+        }
+        Ok((table.rows.iter())
+            .map(|row| indices.iter().map(|i| row[*i].clone()).collect())
+            .collect())
+    }
+
+    /// Returns the rows of the child sorted by the keys.
+    fn order(&self, keys: Id, child: Id) -> Result<Vec<Row>, Error> {
+        let input = self.schema(child);
+        let mut rows = vec![];
+        for row in self.plan(child)? {
+            rows.push((self.sort_key(keys, &input, &row)?, row));
+        }
+        rows.sort_by(|(a, _), (b, _)| compare(a, b));
+        Ok(rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// Returns the values of the order keys on the row.
+    fn sort_key(&self, keys: Id, input: &[Id], row: &Row) -> Result<SortKey, Error> {
+        (self.list(keys).iter())
+            .map(|key| match self.node(*key) {
+                Expr::Desc(key) => Ok((self.eval(*key, input, row)?, true)),
+                Expr::Asc(key) => Ok((self.eval(*key, input, row)?, false)),
+                _ => Ok((self.eval(*key, input, row)?, false)),
+            })
+            .collect()
+    }
+
+    fn limit(&self, limit: Id, offset: Id, rows: Vec<Row>) -> Result<Vec<Row>, Error> {
+        let count = |id: Id| match self.eval(id, &[], &vec![])? {
+            Value::Null => Ok(None),
+            Value::Int(n) => Ok(Some(n.max(0) as usize)),
+            _ => Err(self.unsupported(id)),
+        };
+        let limit = count(limit)?.unwrap_or(usize::MAX);
+        let offset = count(offset)?.unwrap_or(0);
+        Ok(rows.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Joins the rows of two children that satisfy `on`.
+    fn join(
+        &self,
+        ty: Id,
+        left: Id,
+        right: Id,
+        on: impl Fn(&Row, &Row) -> Result<bool, Error>,
+    ) -> Result<Vec<Row>, Error> {
+        let (lrows, rrows) = (self.plan(left)?, self.plan(right)?);
+        let lnulls = vec![Value::Null; self.schema(left).len()];
+        let rnulls = vec![Value::Null; self.schema(right).len()];
+        let ty = self.node(ty);
+        let mut rmatched = vec![false; rrows.len()];
+        let mut rows = vec![];
+        for lrow in lrows.iter() {
+            let mut matched = false;
+            for (i, rrow) in rrows.iter().enumerate() {
+                if on(lrow, rrow)? {
+                    rows.push([lrow.clone(), rrow.clone()].concat());
+                    matched = true;
+                    rmatched[i] = true;
+                }
+            }
+            if !matched && matches!(ty, Expr::LeftOuter | Expr::FullOuter) {
+                rows.push([lrow.clone(), rnulls.clone()].concat());
+            }
+        }
+        if matches!(ty, Expr::RightOuter | Expr::FullOuter) {
+            for (rrow, matched) in rrows.iter().zip(rmatched) {
+                if !matched {
+                    rows.push([lnulls.clone(), rrow.clone()].concat());
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Groups the rows of the child by each grouping set of `keys`,
+    /// and returns the values of the aggregations followed by the keys of each group.
+    ///
+    /// `aggregate` is called with each aggregation, the keys in the grouping set
+    /// and the rows in the group.
+    fn group(
+        &self,
+        aggs: Id,
+        keys: Id,
+        child: Id,
+        aggregate: impl Fn(Id, &[Id], &[&Row]) -> Result<Value, Error>,
+    ) -> Result<Vec<Row>, Error> {
+        let input = self.schema(child);
+        let rows = self.plan(child)?;
+        let sets: Vec<&[Id]> = match self.node(keys) {
+            Expr::List(keys) => vec![keys],
+            Expr::GroupingSets(sets) => self.list(*sets).iter().map(|s| self.list(*s)).collect(),
+            _ => return Err(self.unsupported(keys)),
+        };
+        let all_keys = self.schema(keys);
+        let mut output = vec![];
+        for set in sets {
+            let set_eclasses: Vec<Id> = set.iter().map(|id| self.eclass(*id)).collect();
+            let mut groups: Vec<(Row, Vec<&Row>)> = vec![];
+            let mut index = HashMap::new();
+            for row in rows.iter() {
+                let key = self.eval_ids(set, &input, row)?;
+                let i = *index.entry(key.clone()).or_insert_with(|| {
+                    groups.push((key, vec![]));
+                    groups.len() - 1
+                });
+                groups[i].1.push(row);
+            }
+            // aggregate over all rows into one group
+            if set.is_empty() && groups.is_empty() {
+                groups.push((vec![], vec![]));
+            }
+            for (key, rows) in groups {
+                let mut values = vec![];
+                for agg in self.list(aggs) {
+                    values.push(aggregate(*agg, &set_eclasses, &rows)?);
+                }
+                // keys not in the grouping set are null
+                for id in all_keys.iter() {
+                    values.push(match set_eclasses.iter().position(|k| k == id) {
+                        Some(i) => key[i].clone(),
+                        None => Value::Null,
+                    });
+                }
+                output.push(values);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Returns the value of the aggregation over the rows.
+    fn aggregate(&self, agg: Id, input: &[Id], rows: &[&Row]) -> Result<Value, Error> {
+        use Expr::*;
+        let values = |a: &Id| -> Result<Vec<Value>, Error> {
+            let mut values = vec![];
+            for row in rows {
+                let v = self.eval(*a, input, row)?;
+                if !v.is_null() {
+                    values.push(v);
+                }
+            }
+            Ok(values)
+        };
+        Ok(match self.node(agg) {
+            Max(a) => values(a)?.into_iter().max().unwrap_or(Value::Null),
+            Min(a) => values(a)?.into_iter().min().unwrap_or(Value::Null),
+            Sum(a) => self.sum(agg, values(a)?)?,
+            SumDistinct(a) => self.sum(agg, distinct(values(a)?))?,
+            Avg(a) => {
+                let values = values(a)?;
+                let count = Value::Int(values.len() as i32);
+                match values.is_empty() {
+                    true => Value::Null,
+                    false => self.arith(agg, self.sum(agg, values)?, count, i32::checked_div)?,
+                }
+            }
+            Count(a) => Value::Int(values(a)?.len() as i32),
+            CountDistinct(a) => Value::Int(distinct(values(a)?).len() as i32),
+            RowCount => Value::Int(rows.len() as i32),
+            _ => return Err(self.unsupported(agg)),
+        })
+    }
+
+    /// Returns the value of the aggregation by merging its partial states in the rows.
+    /// See [`agg::agg_states`].
+    fn final_aggregate(&self, agg: Id, input: &[Id], rows: &[&Row]) -> Result<Value, Error> {
+        use Expr::*;
+        let states = |state: Option<Id>| -> Result<Vec<Value>, Error> {
+            let i = (state.and_then(|s| input.iter().position(|id| *id == self.egraph.find(s))))
+                .ok_or_else(|| self.unsupported(agg))?;
+            Ok(rows
+                .iter()
+                .map(|row| row[i].clone())
+                .filter(|v| !v.is_null())
+                .collect())
+        };
+        let this = Some(self.eclass(agg));
+        Ok(match self.node(agg) {
+            Max(_) => states(this)?.into_iter().max().unwrap_or(Value::Null),
+            Min(_) => states(this)?.into_iter().min().unwrap_or(Value::Null),
+            Sum(_) => self.sum(agg, states(this)?)?,
+            Count(_) | RowCount => match self.sum(agg, states(this)?)? {
+                Value::Null => Value::Int(0),
+                count => count,
+            },
+            Avg(a) => {
+                let a = self.eclass(*a);
+                let sum = self.sum(agg, states(self.egraph.lookup(Sum(a)))?)?;
+                let count = self.sum(agg, states(self.egraph.lookup(Count(a)))?)?;
+                match count {
+                    Value::Null | Value::Int(0) => Value::Null,
+                    count => self.arith(agg, sum, count, i32::checked_div)?,
+                }
+            }
+            _ => return Err(self.unsupported(agg)),
+        })
+    }
+
+    /// Returns the rows of the child followed by the values of the window functions.
+    fn window(&self, overs: Id, child: Id) -> Result<Vec<Row>, Error> {
+        use Expr::*;
+        let input = self.schema(child);
+        let mut rows = self.plan(child)?;
+        for over in self.list(overs) {
+            let Over([f, partition, order]) = self.node(*over) else {
+                return Err(self.unsupported(*over));
+            };
+            // the rows of each partition, sorted by the order keys
+            let mut partitions: Vec<Vec<(SortKey, usize)>> = vec![];
+            let mut index = HashMap::new();
+            for (i, row) in rows.iter().enumerate() {
+                let key = self.eval_list(*partition, &input, row)?;
+                let p = *index.entry(key).or_insert_with(|| {
+                    partitions.push(vec![]);
+                    partitions.len() - 1
+                });
+                partitions[p].push((self.sort_key(*order, &input, row)?, i));
+            }
+            let mut values = vec![Value::Null; rows.len()];
+            for mut partition in partitions {
+                partition.sort_by(|(a, _), (b, _)| compare(a, b));
+                let mut rank = 0;
+                let mut dense_rank = 0;
+                for (pos, (key, i)) in partition.iter().enumerate() {
+                    let first_peer = partition.iter().position(|(k, _)| k == key).unwrap();
+                    let last_peer = partition.iter().rposition(|(k, _)| k == key).unwrap();
+                    if first_peer == pos {
+                        rank = pos + 1;
+                        dense_rank += 1;
+                    }
+                    values[*i] = match self.node(*f) {
+                        RowNumber => Value::Int(pos as i32 + 1),
+                        Rank => Value::Int(rank as i32),
+                        DenseRank => Value::Int(dense_rank),
+                        // the frame is from the first row to the last peer of the current row
+                        _ => {
+                            let frame: Vec<&Row> = (partition[..=last_peer].iter())
+                                .map(|(_, i)| &rows[*i])
+                                .collect();
+                            self.aggregate(*f, &input, &frame)?
+                        }
+                    };
+                }
+            }
+            for (row, value) in rows.iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+        Ok(rows)
+    }
+
+    fn eval_list(&self, list: Id, input: &[Id], row: &Row) -> Result<Row, Error> {
+        self.eval_ids(self.list(list), input, row)
+    }
+
+    fn eval_ids(&self, ids: &[Id], input: &[Id], row: &Row) -> Result<Row, Error> {
+        ids.iter().map(|id| self.eval(*id, input, row)).collect()
+    }
+
+    /// Returns the value of the expression on a row of the input.
+    fn eval(&self, id: Id, input: &[Id], row: &Row) -> Result<Value, Error> {
+        use Expr::*;
+        if let Some(i) = input.iter().position(|x| *x == self.eclass(id)) {
+            return Ok(row[i].clone());
+        }
+        let x = |id: &Id| self.eval(*id, input, row);
+        // comparisons with null are null
+        let cmp = |a: &Id, b: &Id, f: fn(Ordering) -> bool| -> Result<Value, Error> {
+            let (a, b) = (x(a)?, x(b)?);
+            Ok(match a.is_null() || b.is_null() {
+                true => Value::Null,
+                false => f(a.cmp(&b)).into(),
+            })
+        };
+        Ok(match self.node(id) {
+            Constant(v) => v.clone(),
+            ColumnIndex(i) => (row.get(i.0 as usize).cloned())
+                .ok_or_else(|| Error::ColumnNotFound(i.to_string()))?,
+            Column(c) => return Err(Error::ColumnNotFound(c.to_string())),
+            Nested(a) => x(a)?,
+            Neg(a) => match x(a)? {
+                Value::Null => Value::Null,
+                Value::Int(a) => Value::Int(a.checked_neg().ok_or_else(|| self.overflow(id))?),
+                _ => return Err(self.type_mismatch(id)),
+            },
+            Not(a) => match x(a)? {
+                a @ (Value::Null | Value::Bool(_)) => !a,
+                _ => return Err(self.type_mismatch(id)),
+            },
+            IsNull(a) => x(a)?.is_null().into(),
+            Add([a, b]) => self.arith(id, x(a)?, x(b)?, i32::checked_add)?,
+            Sub([a, b]) => self.arith(id, x(a)?, x(b)?, i32::checked_sub)?,
+            Mul([a, b]) => self.arith(id, x(a)?, x(b)?, i32::checked_mul)?,
+            Div([a, b]) => {
+                let (a, b) = (x(a)?, x(b)?);
+                if b.is_zero() {
+                    return Err(Error::DivisionByZero);
+                }
+                self.arith(id, a, b, i32::checked_div)?
+            }
+            Eq([a, b]) => cmp(a, b, Ordering::is_eq)?,
+            NotEq([a, b]) => cmp(a, b, Ordering::is_ne)?,
+            Gt([a, b]) => cmp(a, b, Ordering::is_gt)?,
+            Lt([a, b]) => cmp(a, b, Ordering::is_lt)?,
+            GtEq([a, b]) => cmp(a, b, Ordering::is_ge)?,
+            LtEq([a, b]) => cmp(a, b, Ordering::is_le)?,
+            And([a, b]) => self.logic(id, x(a)?, x(b)?, Value::and)?,
+            Or([a, b]) => self.logic(id, x(a)?, x(b)?, Value::or)?,
+            Xor([a, b]) => self.logic(id, x(a)?, x(b)?, Value::xor)?,
+            // aggregations and window functions must be computed by the child
+            _ => return Err(self.unsupported(id)),
+        })
+    }
+
+    /// Applies an integer operator of the expression `id`, e.g. [`i32::checked_add`].
+    /// Returns null if either operand is null.
+    fn arith(
+        &self,
+        id: Id,
+        a: Value,
+        b: Value,
+        f: fn(i32, i32) -> Option<i32>,
+    ) -> Result<Value, Error> {
+        match (a, b) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Int(a), Value::Int(b)) => {
+                f(a, b).map(Value::Int).ok_or_else(|| self.overflow(id))
+            }
+            _ => Err(self.type_mismatch(id)),
+        }
+    }
+
+    /// Applies a logical operator of the expression `id` on booleans or nulls.
+    fn logic(
+        &self,
+        id: Id,
+        a: Value,
+        b: Value,
+        f: fn(&Value, &Value) -> Value,
+    ) -> Result<Value, Error> {
+        let is_bool = |v: &Value| matches!(v, Value::Null | Value::Bool(_));
+        match is_bool(&a) && is_bool(&b) {
+            true => Ok(f(&a, &b)),
+            false => Err(self.type_mismatch(id)),
+        }
+    }
+
+    /// Returns the sum of the non-null values of the aggregation `agg`,
+    /// or null if there is no value.
+    fn sum(&self, agg: Id, values: Vec<Value>) -> Result<Value, Error> {
+        (values.into_iter()).try_fold(Value::Null, |sum, v| match sum {
+            Value::Null => self.arith(agg, Value::Int(0), v, i32::checked_add),
+            sum => self.arith(agg, sum, v, i32::checked_add),
+        })
+    }
+}
+
+/// Compares two sort keys. Nulls are the smallest values.
+fn compare(a: &[(Value, bool)], b: &[(Value, bool)]) -> Ordering {
+    for ((a, desc), (b, _)) in a.iter().zip(b) {
+        let ord = match desc {
+            true => b.cmp(a),
+            false => a.cmp(b),
+        };
+        if ord.is_ne() {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Removes the duplicates and keeps the first occurrences.
+fn distinct<T: PartialEq>(values: Vec<T>) -> Vec<T> {
+    let mut output = vec![];
+    for v in values {
+        if !output.contains(&v) {
+            output.push(v);
+        }
+    }
+    output
+}
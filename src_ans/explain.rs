@@ -0,0 +1,318 @@
+//! Render a plan as an indented operator tree, like `EXPLAIN` in SQL.
+
+use std::fmt::{self, Display, Formatter};
+
+use egg::{CostFunction, Language};
+
+use super::*;
+use crate::validate::is_plan;
+
+/// The explanation of a plan, which is displayed as an indented operator tree.
+///
+/// # Example
+/// ```text
+/// Projection [t1.a, t2.b]
+///   HashJoin inner on [t1.id = t2.id]
+///     Scan t1 [id, a]
+///     Scan t2 [id, b]
+/// ```
+///
+/// Each operator can be annotated with its estimated rows, cost and schema.
+/// In verbose mode, the expressions of an operator refer to the outputs of its children
+/// by index, e.g. `#0` is the first column of the input.
+pub struct Explain<'a> {
+    expr: &'a RecExpr,
+    analysis: ExprAnalysis,
+    rows: bool,
+    cost: bool,
+    schema: bool,
+    verbose: bool,
+}
+
+impl<'a> Explain<'a> {
+    /// Creates an explanation of the plan without annotations.
+    pub fn new(expr: &'a RecExpr) -> Self {
+        Explain {
+            expr,
+            analysis: ExprAnalysis::default(),
+            rows: false,
+            cost: false,
+            schema: false,
+            verbose: false,
+        }
+    }
+
+    /// Annotates each operator with the estimated number of rows.
+    pub fn with_rows(mut self) -> Self {
+        self.rows = true;
+        self
+    }
+
+    /// Annotates each operator with the cost of its subtree.
+    pub fn with_cost(mut self) -> Self {
+        self.cost = true;
+        self
+    }
+
+    /// Annotates each operator with its output expressions.
+    pub fn with_schema(mut self) -> Self {
+        self.schema = true;
+        self
+    }
+
+    /// Shows the expressions with references to the input columns resolved to indices.
+    pub fn verbose(mut self) -> Self {
+        self.verbose = true;
+        self
+    }
+
+    /// Sets the analysis, which holds the catalog used to estimate the plan.
+    pub fn analysis(mut self, analysis: ExprAnalysis) -> Self {
+        self.analysis = analysis;
+        self
+    }
+}
+
+impl Display for Explain<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.expr.as_ref().is_empty() {
+            return Ok(());
+        }
+        // add the plan to an egraph to get the analysis data of each node
+        let mut egraph = EGraph::new(self.analysis.clone());
+        let mut ids: Vec<Id> = Vec::with_capacity(self.expr.as_ref().len());
+        for node in self.expr.as_ref() {
+            let node = node.clone().map_children(|i| ids[usize::from(i)]);
+            ids.push(egraph.add(node));
+        }
+        let mut costs = HashMap::new();
+        let mut cost_fn = cost::CostFn { egraph: &egraph };
+        for id in ids.iter() {
+            let cost = cost_fn.cost(&egraph[*id].nodes[0], |i| costs[&i]);
+            costs.insert(*id, cost);
+        }
+        let printer = Printer {
+            explain: self,
+            egraph: &egraph,
+            ids,
+            costs,
+        };
+        let root = Id::from(self.expr.as_ref().len() - 1);
+        printer.plan(f, root, 0)
+    }
+}
+
+struct Printer<'a> {
+    explain: &'a Explain<'a>,
+    egraph: &'a EGraph,
+    /// The eclass of each node in the plan.
+    ids: Vec<Id>,
+    /// The cost of each eclass.
+    costs: HashMap<Id, f32>,
+}
+
+impl Printer<'_> {
+    fn node(&self, id: Id) -> &Expr {
+        &self.explain.expr[id]
+    }
+
+    fn data(&self, id: Id) -> &Data {
+        &self.egraph[self.ids[usize::from(id)]].data
+    }
+
+    /// Returns the output expressions of the plan, which are eclasses in the egraph.
+    fn schema(&self, id: Id) -> Vec<Id> {
+        self.data(id).schema.clone().unwrap_or_default()
+    }
+
+    /// Writes the plan rooted at `id` with the indentation of `depth`.
+    fn plan(&self, f: &mut Formatter<'_>, id: Id, depth: usize) -> fmt::Result {
+        let enode = self.node(id);
+        if !is_plan(enode) {
+            return writeln!(f, "{}", self.expr(id, &[]));
+        }
+        write!(f, "{:indent$}{}", "", self.operator(id), indent = depth * 2)?;
+        let mut annotations = vec![];
+        if self.explain.rows {
+            annotations.push(format!("rows={}", self.data(id).rows));
+        }
+        if self.explain.cost {
+            let cost = self.costs[&self.ids[usize::from(id)]];
+            annotations.push(format!("cost={cost}"));
+        }
+        if self.explain.schema {
+            let schema = (self.schema(id).into_iter())
+                .map(|id| self.eclass(id))
+                .collect::<Vec<_>>();
+            annotations.push(format!("schema=[{}]", schema.join(", ")));
+        }
+        if !annotations.is_empty() {
+            write!(f, " ({})", annotations.join(", "))?;
+        }
+        writeln!(f)?;
+        for child in enode.children() {
+            if is_plan(self.node(*child)) {
+                self.plan(f, *child, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name and arguments of the plan node.
+    fn operator(&self, id: Id) -> String {
+        use Expr::*;
+        let enode = self.node(id);
+        // the outputs of all children
+        let input: Vec<Id> = match self.explain.verbose {
+            true => (enode.children().iter())
+                .filter(|c| is_plan(self.node(**c)))
+                .flat_map(|c| self.schema(*c))
+                .collect(),
+            false => vec![],
+        };
+        let e = |id: &Id| self.expr(*id, &input);
+        match enode {
+            Scan([table, columns]) => {
+                let table = self.node(*table).to_string();
+                let prefix = format!("{table}.");
+                let columns = (self.list(*columns).iter())
+                    .map(|c| {
+                        let c = self.expr(*c, &[]);
+                        c.strip_prefix(&prefix).map(String::from).unwrap_or(c)
+                    })
+                    .collect::<Vec<_>>();
+                format!("Scan {table} [{}]", columns.join(", "))
+            }
+            Values(rows) => {
+                let rows = rows.iter().map(e).collect::<Vec<_>>();
+                format!("Values [{}]", rows.join(", "))
+            }
+            Proj([exprs, _]) => format!("Projection {}", e(exprs)),
+            Filter([cond, _]) => format!("Filter {}", e(cond)),
+            Order([keys, _]) => format!("Order {}", e(keys)),
+            Limit([limit, offset, _]) => format!("Limit {} offset {}", e(limit), e(offset)),
+            TopN([limit, offset, keys, _]) => {
+                format!("TopN {} offset {} {}", e(limit), e(offset), e(keys))
+            }
+            Join([ty, cond, _, _]) => format!("Join {} on {}", self.node(*ty), e(cond)),
+            NestedLoopJoin([ty, cond, _, _]) => {
+                format!("NestedLoopJoin {} on {}", self.node(*ty), e(cond))
+            }
+            HashJoin([ty, lkeys, rkeys, left, right])
+            | MergeJoin([ty, lkeys, rkeys, left, right]) => {
+                let name = match enode {
+                    HashJoin(_) => "HashJoin",
+                    _ => "MergeJoin",
+                };
+                // the keys refer to their own side
+                let (linput, rinput) = match self.explain.verbose {
+                    true => (self.schema(*left), self.schema(*right)),
+                    false => (vec![], vec![]),
+                };
+                let keys = (self.list(*lkeys).iter())
+                    .zip(self.list(*rkeys))
+                    .map(|(l, r)| format!("{} = {}", self.expr(*l, &linput), self.expr(r, &rinput)))
+                    .collect::<Vec<_>>();
+                format!("{name} {} on [{}]", self.node(*ty), keys.join(", "))
+            }
+            Agg([aggs, keys, _]) => format!("Aggregate {} group by {}", e(aggs), e(keys)),
+            FinalAgg([aggs, keys, _]) => {
+                format!("FinalAggregate {} group by {}", e(aggs), e(keys))
+            }
+            Window([exprs, _]) => format!("Window {}", e(exprs)),
+            Distinct(_) => "Distinct".into(),
+            Union(_) => "Union".into(),
+            UnionAll(_) => "UnionAll".into(),
+            Intersect(_) => "Intersect".into(),
+            Except(_) => "Except".into(),
+            Empty(c) if is_plan(self.node(*c)) => "Empty".into(),
+            Empty(c) => format!("Empty {}", e(c)),
+            _ => panic!("not a plan: {enode}"),
+        }
+    }
+
+    /// Returns the elements if the node is a list, otherwise the node itself.
+    fn list(&self, id: Id) -> Vec<Id> {
+        match self.node(id) {
+            Expr::List(list) => list.to_vec(),
+            _ => vec![id],
+        }
+    }
+
+    /// Renders the expression `id` in infix notation.
+    /// Subexpressions in `input` are rendered as their indices.
+    fn expr(&self, id: Id, input: &[Id]) -> String {
+        if let Some(i) = (input.iter()).position(|x| *x == self.ids[usize::from(id)]) {
+            return ColumnIndex(i as u32).to_string();
+        }
+        render(self.node(id), &|id| self.expr(id, input), &|id| {
+            self.node(id)
+        })
+    }
+
+    /// Renders the expression of an eclass in the egraph.
+    fn eclass(&self, id: Id) -> String {
+        let expr = self.egraph[id].nodes[0].build_recexpr(|id| self.egraph[id].nodes[0].clone());
+        render_recexpr(&expr, Id::from(expr.as_ref().len() - 1))
+    }
+}
+
+/// Renders the expression `id` in `expr` in infix notation.
+fn render_recexpr(expr: &RecExpr, id: Id) -> String {
+    render(&expr[id], &|id| render_recexpr(expr, id), &|id| &expr[id])
+}
+
+/// Renders the node in infix notation with the rendered children.
+fn render<'a>(enode: &Expr, child: &dyn Fn(Id) -> String, node: &dyn Fn(Id) -> &'a Expr) -> String {
+    use Expr::*;
+    let e = |id: &Id| child(*id);
+    // wrap binary operations with parentheses
+    let p = |id: &Id| match node(*id) {
+        Add(_) | Sub(_) | Mul(_) | Div(_) | Eq(_) | NotEq(_) | Gt(_) | Lt(_) | GtEq(_)
+        | LtEq(_) | And(_) | Or(_) | Xor(_) => format!("({})", child(*id)),
+        _ => child(*id),
+    };
+    let join = |ids: &[Id]| ids.iter().map(e).collect::<Vec<_>>().join(", ");
+    match enode {
+        Constant(v) => v.to_string(),
+        ColumnIndex(i) => i.to_string(),
+        Column(c) => c.to_string(),
+        Nested(a) => e(a),
+        List(list) => format!("[{}]", join(list)),
+        Neg(a) => format!("-{}", p(a)),
+        Not(a) => format!("not {}", p(a)),
+        IsNull(a) => format!("{} is null", p(a)),
+        Add([a, b]) | Sub([a, b]) | Mul([a, b]) | Div([a, b]) | Eq([a, b]) | NotEq([a, b])
+        | Gt([a, b]) | Lt([a, b]) | GtEq([a, b]) | LtEq([a, b]) | And([a, b]) | Or([a, b])
+        | Xor([a, b]) => format!("{} {enode} {}", p(a), p(b)),
+        Max(a) | Min(a) | Sum(a) | Avg(a) | Count(a) | Grouping(a) => format!("{enode}({})", e(a)),
+        RowCount => "count(*)".into(),
+        CountDistinct(a) => format!("count(distinct {})", e(a)),
+        SumDistinct(a) => format!("sum(distinct {})", e(a)),
+        RowNumber | Rank | DenseRank => format!("{enode}()"),
+        Over([f, partition, order]) => {
+            let mut spec = vec![];
+            if !matches!(node(*partition), List(l) if l.is_empty()) {
+                spec.push(format!("partition by {}", e(partition)));
+            }
+            if !matches!(node(*order), List(l) if l.is_empty()) {
+                spec.push(format!("order by {}", e(order)));
+            }
+            format!("{} over ({})", e(f), spec.join(" "))
+        }
+        Asc(a) => format!("{} asc", e(a)),
+        Desc(a) => format!("{} desc", e(a)),
+        Alias([a, name]) => format!("{} as {}", e(a), e(name)),
+        GroupingSets(sets) => format!("grouping sets {}", e(sets)),
+        Rollup(list) => format!("rollup {}", e(list)),
+        Cube(list) => format!("cube {}", e(list)),
+        // other nodes are rendered as S-expressions
+        _ => {
+            let children = enode.children().iter().map(e).collect::<Vec<_>>();
+            match children.is_empty() {
+                true => enode.to_string(),
+                false => format!("({enode} {})", children.join(" ")),
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Export plans and egraphs to Graphviz DOT and JSON for visualization and external tools.
+//!
+//! # JSON format
+//!
+//! A plan is a tree of nodes:
+//!
+//! ```text
+//! {"op": "proj", "children": [{"op": "list", "children": [...]}, ...]}
+//! ```
+//!
+//! An egraph is a list of eclasses ordered by id. Each eclass has its enodes,
+//! whose children are eclass ids, and the analysis data:
+//!
+//! ```text
+//! {"classes": [
+//!   {"id": 0, "nodes": [{"op": "a", "children": []}],
+//!    "data": {"constant": null, "columns": ["a"], "schema": null}},
+//!   ...
+//! ]}
+//! ```
+//!
+//! - `constant` is the constant value in the plan syntax, e.g. `"1"`, `"'hello'"`, `"null"`,
+//!   or `null` if the expression is not a constant.
+//! - `columns` is sorted.
+//! - `schema` is the list of eclass ids, or `null` for non-plan nodes.
+
+use std::fmt::Write;
+
+use egg::Language;
+
+use super::*;
+
+/// Returns the plan in Graphviz DOT format.
+pub fn plan_to_dot(expr: &RecExpr) -> String {
+    let mut dot = String::from("digraph plan {\n");
+    dot += "  node [shape=box];\n";
+    for (i, node) in expr.as_ref().iter().enumerate() {
+        writeln!(dot, "  n{i} [label={}];", dot_string(&node.to_string())).unwrap();
+    }
+    for (i, node) in expr.as_ref().iter().enumerate() {
+        for (j, child) in node.children().iter().enumerate() {
+            writeln!(dot, "  n{i} -> n{child} [label={j}];").unwrap();
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+/// Returns the plan in JSON format, or `null` if the plan is empty.
+pub fn plan_to_json(expr: &RecExpr) -> String {
+    fn node(expr: &RecExpr, id: Id, json: &mut String) {
+        let enode = &expr[id];
+        write!(
+            json,
+            "{{\"op\": {}, \"children\": [",
+            json_string(&enode.to_string())
+        )
+        .unwrap();
+        for (i, child) in enode.children().iter().enumerate() {
+            if i > 0 {
+                *json += ", ";
+            }
+            node(expr, *child, json);
+        }
+        *json += "]}";
+    }
+    if expr.as_ref().is_empty() {
+        return "null".into();
+    }
+    let mut json = String::new();
+    node(expr, Id::from(expr.as_ref().len() - 1), &mut json);
+    json
+}
+
+/// Returns the egraph in Graphviz DOT format.
+///
+/// Each eclass is a cluster labeled with its id and analysis data.
+/// The edges point from an enode to the cluster of its child.
+pub fn egraph_to_dot(egraph: &EGraph) -> String {
+    let mut dot = String::from("digraph egraph {\n");
+    dot += "  compound=true;\n";
+    dot += "  clusterrank=local;\n";
+    for class in classes(egraph) {
+        let mut label = format!("#{}", class.id);
+        let data = &class.data;
+        if let Some(constant) = &data.constant {
+            write!(label, "\nconstant: {constant}").unwrap();
+        }
+        if !data.columns.is_empty() {
+            write!(label, "\ncolumns: [{}]", columns(&data.columns).join(", ")).unwrap();
+        }
+        if let Some(schema) = &data.schema {
+            let schema: Vec<_> = (schema.iter())
+                .map(|id| format!("#{}", egraph.find(*id)))
+                .collect();
+            write!(label, "\nschema: [{}]", schema.join(", ")).unwrap();
+        }
+        writeln!(dot, "  subgraph cluster_{} {{", class.id).unwrap();
+        writeln!(dot, "    style=dotted;").unwrap();
+        writeln!(dot, "    label={};", dot_string(&label)).unwrap();
+        for (i, node) in class.iter().enumerate() {
+            let label = dot_string(&node.to_string());
+            writeln!(dot, "    \"{}.{i}\" [label={label}];", class.id).unwrap();
+        }
+        dot += "  }\n";
+    }
+    for class in classes(egraph) {
+        for (i, node) in class.iter().enumerate() {
+            for (j, child) in node.children().iter().enumerate() {
+                let child = egraph.find(*child);
+                writeln!(
+                    dot,
+                    "  \"{}.{i}\" -> \"{child}.0\" [lhead=cluster_{child}, label={j}];",
+                    class.id
+                )
+                .unwrap();
+            }
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+/// Returns the egraph in JSON format.
+pub fn egraph_to_json(egraph: &EGraph) -> String {
+    let mut json = String::from("{\"classes\": [");
+    for (i, class) in classes(egraph).into_iter().enumerate() {
+        if i > 0 {
+            json += ", ";
+        }
+        write!(json, "{{\"id\": {}, \"nodes\": [", class.id).unwrap();
+        for (j, node) in class.iter().enumerate() {
+            if j > 0 {
+                json += ", ";
+            }
+            let children: Vec<_> = (node.children().iter())
+                .map(|id| egraph.find(*id).to_string())
+                .collect();
+            write!(
+                json,
+                "{{\"op\": {}, \"children\": [{}]}}",
+                json_string(&node.to_string()),
+                children.join(", ")
+            )
+            .unwrap();
+        }
+        let data = &class.data;
+        let constant = match &data.constant {
+            Some(constant) => json_string(&constant.to_string()),
+            None => "null".into(),
+        };
+        let columns: Vec<_> = columns(&data.columns)
+            .iter()
+            .map(|c| json_string(c))
+            .collect();
+        let schema = match &data.schema {
+            Some(schema) => {
+                let schema: Vec<_> = (schema.iter())
+                    .map(|id| egraph.find(*id).to_string())
+                    .collect();
+                format!("[{}]", schema.join(", "))
+            }
+            None => "null".into(),
+        };
+        write!(
+            json,
+            "], \"data\": {{\"constant\": {constant}, \"columns\": [{}], \"schema\": {schema}}}}}",
+            columns.join(", ")
+        )
+        .unwrap();
+    }
+    json += "]}";
+    json
+}
+
+/// Returns the eclasses ordered by id.
+fn classes(egraph: &EGraph) -> Vec<&egg::EClass<Expr, Data>> {
+    let mut classes: Vec<_> = egraph.classes().collect();
+    classes.sort_by_key(|class| class.id);
+    classes
+}
+
+/// Returns the sorted names of columns.
+fn columns(columns: &plan::ColumnSet) -> Vec<String> {
+    let mut columns: Vec<_> = columns.iter().map(|c| c.to_string()).collect();
+    columns.sort();
+    columns
+}
+
+/// Returns the string as a quoted DOT identifier.
+fn dot_string(s: &str) -> String {
+    let s = s.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", s.replace('\n', "\\n"))
+}
+
+/// Returns the string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json += "\"";
+    json
+}
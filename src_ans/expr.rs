@@ -1,6 +1,6 @@
 //! Expression simplification rules and constant folding.
 
-use egg::{rewrite as rw, Subst, Var};
+use egg::{Subst, Var};
 
 use super::*;
 
@@ -99,6 +99,8 @@ pub fn eval_constant(egraph: &EGraph, enode: &Expr) -> ConstValue {
     Some(match enode {
         Constant(v) => v.clone(),
         Column(_) => return None,
+        // unknown until bound, so a plan with parameters can be optimized once
+        Param(_) => return None,
         List(_) => return None,
         Neg(a) => -x(a)?.clone(),
         Not(a) => !x(a)?.clone(),
@@ -132,7 +134,7 @@ pub fn eval_constant(egraph: &EGraph, enode: &Expr) -> ConstValue {
 pub fn union_constant(egraph: &mut EGraph, id: Id) {
     if let Some(val) = &egraph[id].data.constant {
         let added = egraph.add(Expr::Constant(val.clone()));
-        egraph.union(id, added);
+        egraph.union_trusted(id, added, "constant-folding");
     }
 }
 
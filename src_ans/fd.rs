@@ -0,0 +1,226 @@
+//! Analyze functional dependencies and unique keys of plan nodes.
+
+use std::collections::HashSet;
+
+use egg::Subst;
+
+use super::*;
+
+/// The data type of functional dependency analysis.
+///
+/// All expressions are the ones in the schema of the plan node.
+/// It is always empty for non-plan nodes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FuncDeps {
+    /// Sets of expressions whose values are unique among the rows.
+    pub keys: Vec<Vec<Id>>,
+
+    /// Functional dependencies `(from, to)`:
+    /// rows with the same values of `from` have the same values of `to`.
+    pub deps: Vec<(Vec<Id>, Vec<Id>)>,
+}
+
+/// Returns the functional dependencies and unique keys of the plan node.
+pub fn analyze_fds(egraph: &EGraph, enode: &Expr) -> FuncDeps {
+    use Expr::*;
+    let x = |i: &Id| egraph[*i].data.fds.clone();
+    let schema = |i: &Id| egraph[*i].data.schema.clone().unwrap_or_default();
+    match enode {
+        // the primary key of the table
+        Scan([table, columns]) => {
+            let columns = schema(columns);
+            let Some(key) = primary_key(egraph, *table, &columns) else {
+                return FuncDeps::default();
+            };
+            FuncDeps {
+                keys: vec![key.clone()],
+                deps: vec![(key, columns)],
+            }
+        }
+        Filter([cond, c]) => {
+            let mut fds = x(c);
+            fds.deps.extend(equalities(egraph, *cond));
+            fds
+        }
+        Order([_, c]) | Limit([_, _, c]) | TopN([_, _, _, c]) | Window([_, c]) | Empty(c) => x(c),
+        Proj([exprs, c]) => x(c).project(&schema(exprs)),
+        // the group keys are unique, unless there are grouping sets
+        Agg([aggs, group_keys, _]) | FinalAgg([aggs, group_keys, _]) => {
+            if !egraph[*group_keys].iter().any(|e| matches!(e, List(_))) {
+                return FuncDeps::default();
+            }
+            let keys = schema(group_keys);
+            FuncDeps {
+                keys: vec![keys.clone()],
+                deps: vec![(keys, schema(aggs))],
+            }
+        }
+        Join([ty, cond, l, r]) | NestedLoopJoin([ty, cond, l, r]) => {
+            join(egraph, *ty, *l, *r, equalities(egraph, *cond))
+        }
+        HashJoin([ty, lkeys, rkeys, l, r]) | MergeJoin([ty, lkeys, rkeys, l, r]) => {
+            let eqs = (schema(lkeys).into_iter())
+                .zip(schema(rkeys))
+                .flat_map(|(a, b)| [(vec![a], vec![b]), (vec![b], vec![a])])
+                .collect();
+            join(egraph, *ty, *l, *r, eqs)
+        }
+        // the output rows are distinct
+        Distinct(c) | Intersect([c, _]) | Except([c, _]) => {
+            let mut fds = x(c);
+            fds.add_key(schema(c));
+            fds
+        }
+        Union([l, _]) => FuncDeps {
+            keys: vec![schema(l)],
+            deps: vec![],
+        },
+        _ => FuncDeps::default(),
+    }
+}
+
+/// Returns the columns of the primary key of `table` in the catalog,
+/// or `None` if there is no primary key or some of its columns are not scanned.
+fn primary_key(egraph: &EGraph, table: Id, columns: &[Id]) -> Option<Vec<Id>> {
+    let table = egraph[table].iter().find_map(|e| match e {
+        Expr::Column(name) => Some(*name),
+        _ => None,
+    })?;
+    let key = egraph.analysis.primary_keys.get(&table)?;
+    (key.iter())
+        .map(|col| {
+            (columns.iter().cloned()).find(|id| egraph[*id].nodes.contains(&Expr::Column(*col)))
+        })
+        .collect()
+}
+
+/// Returns the functional dependencies given by the equality conjuncts in the condition.
+fn equalities(egraph: &EGraph, cond: Id) -> Vec<(Vec<Id>, Vec<Id>)> {
+    let mut deps = vec![];
+    let mut visited = HashSet::new();
+    let mut stack = vec![cond];
+    while let Some(id) = stack.pop() {
+        if !visited.insert(egraph.find(id)) {
+            continue;
+        }
+        for node in egraph[id].iter() {
+            match node {
+                Expr::And([a, b]) => stack.extend([*a, *b]),
+                Expr::Eq([a, b]) => {
+                    for (a, b) in [(*a, *b), (*b, *a)] {
+                        match egraph[b].data.constant {
+                            // the expression is a constant
+                            Some(_) => deps.push((vec![], vec![a])),
+                            None => deps.push((vec![b], vec![a])),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    deps
+}
+
+/// Returns the functional dependencies and unique keys of an inner join.
+///
+/// A key of one side is a key of the join if it determines a key of the other side,
+/// since each row on this side matches at most one row on the other side.
+fn join(egraph: &EGraph, ty: Id, left: Id, right: Id, eqs: Vec<(Vec<Id>, Vec<Id>)>) -> FuncDeps {
+    if !egraph[ty].nodes.contains(&Expr::Inner) {
+        return FuncDeps::default();
+    }
+    let l = &egraph[left].data.fds;
+    let r = &egraph[right].data.fds;
+    let mut fds = FuncDeps {
+        keys: vec![],
+        deps: (l.deps.iter().chain(&r.deps).cloned()).chain(eqs).collect(),
+    };
+    for kl in l.keys.iter() {
+        for kr in r.keys.iter() {
+            fds.add_key(kl.iter().chain(kr).cloned().collect());
+        }
+    }
+    for (keys, other) in [(&l.keys, &r.keys), (&r.keys, &l.keys)] {
+        for key in keys {
+            let closure = fds.closure(key);
+            if other
+                .iter()
+                .any(|k| k.iter().all(|id| closure.contains(id)))
+            {
+                fds.add_key(key.clone());
+            }
+        }
+    }
+    fds
+}
+
+impl FuncDeps {
+    /// Returns all expressions determined by `exprs`, including themselves.
+    pub fn closure(&self, exprs: &[Id]) -> Vec<Id> {
+        let mut closure = exprs.to_vec();
+        loop {
+            let mut changed = false;
+            for (from, to) in self.deps.iter() {
+                if from.iter().all(|id| closure.contains(id)) {
+                    for id in to {
+                        if !closure.contains(id) {
+                            closure.push(*id);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return closure;
+            }
+        }
+    }
+
+    /// Keeps the keys and dependencies on the expressions in `exprs`.
+    fn project(mut self, exprs: &[Id]) -> Self {
+        self.keys
+            .retain(|key| key.iter().all(|id| exprs.contains(id)));
+        let deps = std::mem::take(&mut self.deps);
+        for (from, to) in deps {
+            if from.iter().all(|id| exprs.contains(id)) {
+                let to: Vec<_> = to.into_iter().filter(|id| exprs.contains(id)).collect();
+                if !to.is_empty() {
+                    self.deps.push((from, to));
+                }
+            }
+        }
+        self
+    }
+
+    fn add_key(&mut self, key: Vec<Id>) {
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+    }
+}
+
+/// Merge two results by keeping all keys and dependencies of both,
+/// since they hold for any node in the eclass.
+pub fn merge(to: &mut FuncDeps, from: FuncDeps) -> DidMerge {
+    let (from_keys, from_deps) = (from.keys.len(), from.deps.len());
+    let (to_keys, to_deps) = (to.keys.len(), to.deps.len());
+    for key in from.keys {
+        to.add_key(key);
+    }
+    for dep in from.deps {
+        if !to.deps.contains(&dep) {
+            to.deps.push(dep);
+        }
+    }
+    DidMerge(
+        to.keys.len() > to_keys || to.deps.len() > to_deps,
+        to.keys.len() > from_keys || to.deps.len() > from_deps,
+    )
+}
+
+/// Returns true if the rows of `plan` are distinct.
+pub fn has_unique_key(plan: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
+    let plan = var(plan);
+    move |egraph, _, subst| !egraph[subst[plan]].data.fds.keys.is_empty()
+}
@@ -0,0 +1,591 @@
+//! Join ordering by dynamic programming.
+//!
+//! Exploring all join orders in the egraph grows exponentially with the number of tables.
+//! This pass reorders joins outside of the egraph, so it can be applied to a [`RecExpr`]
+//! before or between saturation passes:
+//!
+//! ```text
+//! (filter (and (= t1.id t2.id) (= t2.k t3.k))       (proj (list t1.id t1.a t3.k t3.c t2.id t2.k)
+//!   (join inner true                                  (hashjoin inner (list t2.k) (list t3.k)
+//!     (join inner true                         =>       (hashjoin inner (list t1.id) (list t2.id)
+//!       (scan t1 (list t1.id t1.a))                        (scan t1 (list t1.id t1.a))
+//!       (scan t3 (list t3.k t3.c)))                        (scan t2 (list t2.id t2.k)))
+//!     (scan t2 (list t2.id t2.k))))                      (scan t3 (list t3.k t3.c))))
+//! ```
+//!
+//! A region of inner `join`s and `filter`s is flattened into a join graph,
+//! whose vertices are the inputs of the region and edges are the predicates.
+//! The cheapest order is the one with the least sum of intermediate result sizes.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use egg::{AstSize, Extractor, Language};
+
+use crate::cost::{Rows, DEFAULT_SELECTIVITY};
+use crate::plan::ColumnSet;
+
+use super::*;
+
+/// The maximum number of relations ordered by dynamic programming.
+///
+/// Larger regions are ordered greedily.
+pub const DP_THRESHOLD: usize = 10;
+
+/// The maximum number of relations in a region. Larger regions are left unchanged.
+const MAX_RELATIONS: usize = 64;
+
+/// Reorders all regions of inner joins in `expr`.
+///
+/// The output columns of each region keep the original order.
+pub fn reorder_joins(expr: &RecExpr) -> RecExpr {
+    if expr.as_ref().is_empty() {
+        return RecExpr::default();
+    }
+    let mut reorder = JoinOrder::new(expr);
+    reorder.build(Id::from(expr.as_ref().len() - 1));
+    reorder.output
+}
+
+/// Returns the `join`s in `expr` that are Cartesian products,
+/// i.e. no conjunct of the join condition references both sides.
+pub fn cross_products(expr: &RecExpr) -> Vec<Id> {
+    let analysis = JoinOrder::new(expr);
+    (0..expr.as_ref().len())
+        .map(Id::from)
+        .filter(|id| analysis.is_cross_product(*id))
+        .collect()
+}
+
+/// Returns the regions of inner joins in `expr` whose join graph is not connected.
+///
+/// Such regions can not be planned without a Cartesian product.
+pub fn disconnected_relations(expr: &RecExpr) -> Vec<Disconnected> {
+    if expr.as_ref().is_empty() {
+        return vec![];
+    }
+    let analysis = JoinOrder::new(expr);
+    let mut disconnected = vec![];
+    let mut visited = HashSet::new();
+    analysis.find_disconnected(
+        Id::from(expr.as_ref().len() - 1),
+        &mut visited,
+        &mut disconnected,
+    );
+    disconnected
+}
+
+/// A region of inner joins whose join graph is not connected.
+#[derive(Debug)]
+pub struct Disconnected {
+    /// The root of the region in the input expression.
+    pub root: Id,
+    /// The relations in each connected component.
+    pub components: Vec<Vec<RecExpr>>,
+}
+
+impl Display for Disconnected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "relations are not connected by any condition: ")?;
+        for (i, component) in self.components.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, relation) in component.iter().enumerate() {
+                if j != 0 {
+                    write!(f, ", ")?;
+                }
+                // show the table name for scans
+                match relation.as_ref().last() {
+                    Some(Expr::Scan([table, _])) => write!(f, "{}", relation[*table])?,
+                    _ => write!(f, "{relation}")?,
+                }
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A bitset of relations in a region.
+type Relations = u64;
+
+/// Returns the set of the first `n` relations.
+///
+/// Unlike `(1 << n) - 1`, it does not overflow when `n` is [`MAX_RELATIONS`].
+fn first_relations(n: usize) -> Relations {
+    Relations::MAX
+        .checked_shr((Relations::BITS as usize - n) as u32)
+        .unwrap_or(0)
+}
+
+/// A join tree over the relations of a region.
+enum Tree {
+    Leaf(usize),
+    Join(Box<Tree>, Box<Tree>),
+}
+
+/// A region of inner joins.
+struct Region {
+    /// The inputs of the region, in the order of output columns.
+    leaves: Vec<Id>,
+    /// The estimated rows of each leaf, before applying predicates.
+    rows: Vec<Rows>,
+    /// The conjuncts of all conditions, with the relations they reference.
+    preds: Vec<(Id, Relations)>,
+}
+
+impl Region {
+    /// Returns the estimated rows after joining `set` with all applicable predicates.
+    fn rows(&self, set: Relations) -> Rows {
+        let leaves: Rows = (0..self.leaves.len())
+            .filter(|i| set & (1 << i) != 0)
+            .map(|i| self.rows[i])
+            .product();
+        let preds = (self.preds.iter())
+            .filter(|(_, rels)| *rels != 0 && rels & !set == 0)
+            .count();
+        leaves * DEFAULT_SELECTIVITY.powi(preds as i32)
+    }
+
+    /// Returns true if any predicate connects `left` and `right`.
+    fn is_connected(&self, left: Relations, right: Relations) -> bool {
+        (self.preds.iter())
+            .any(|(_, rels)| rels & !(left | right) == 0 && rels & left != 0 && rels & right != 0)
+    }
+
+    /// Returns the join tree with the least cost over all connected subgraphs.
+    ///
+    /// If the join graph is not connected, the best tree of each component is
+    /// combined by cross joins, from the smallest to the largest.
+    /// A component connected only by a predicate over three or more relations
+    /// has no pair of connected subsets, so it is ordered greedily.
+    fn dp(&self) -> Tree {
+        let n = self.leaves.len();
+        let full = first_relations(n);
+        // the cost and the left side of the best tree for each set
+        let mut best: HashMap<Relations, (f32, Relations)> = HashMap::new();
+        for i in 0..n {
+            best.insert(1 << i, (0.0, 0));
+        }
+        for set in 1..=full {
+            if set.count_ones() < 2 {
+                continue;
+            }
+            let lowest = set & set.wrapping_neg();
+            let rows = self.rows(set);
+            // enumerate subsets containing the lowest relation to avoid duplicates
+            let mut left = (set - 1) & set;
+            while left != 0 {
+                let right = set & !left;
+                if left & lowest != 0 && self.is_connected(left, right) {
+                    if let (Some(l), Some(r)) = (best.get(&left), best.get(&right)) {
+                        let cost = l.0 + r.0 + rows;
+                        if best.get(&set).is_none_or(|b| cost < b.0) {
+                            best.insert(set, (cost, left));
+                        }
+                    }
+                }
+                left = (left - 1) & set;
+            }
+        }
+        fn tree(best: &HashMap<Relations, (f32, Relations)>, set: Relations) -> Tree {
+            match best[&set].1 {
+                0 => Tree::Leaf(set.trailing_zeros() as usize),
+                left => Tree::Join(
+                    Box::new(tree(best, left)),
+                    Box::new(tree(best, set & !left)),
+                ),
+            }
+        }
+        let components = self.components();
+        self.cross_join(
+            components
+                .into_iter()
+                .map(|set| match best.contains_key(&set) {
+                    true => (set, tree(&best, set)),
+                    false => (set, self.greedy(set)),
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the join tree of the relations in `set`, built by repeatedly joining
+    /// the two connected trees with the smallest result.
+    fn greedy(&self, set: Relations) -> Tree {
+        let mut trees: Vec<(Relations, Tree)> = (0..self.leaves.len())
+            .filter(|i| set & (1 << i) != 0)
+            .map(|i| (1 << i, Tree::Leaf(i)))
+            .collect();
+        loop {
+            let mut best: Option<(Rows, usize, usize)> = None;
+            for i in 0..trees.len() {
+                for j in i + 1..trees.len() {
+                    let (l, r) = (trees[i].0, trees[j].0);
+                    if !self.is_connected(l, r) {
+                        continue;
+                    }
+                    let rows = self.rows(l | r);
+                    if best.is_none_or(|(b, _, _)| rows < b) {
+                        best = Some((rows, i, j));
+                    }
+                }
+            }
+            let Some((_, i, j)) = best else {
+                return self.cross_join(trees);
+            };
+            let (r_set, r_tree) = trees.remove(j);
+            let (l_set, l_tree) = trees.remove(i);
+            trees.push((
+                l_set | r_set,
+                Tree::Join(Box::new(l_tree), Box::new(r_tree)),
+            ));
+        }
+    }
+
+    /// Returns the connected components of the join graph.
+    fn components(&self) -> Vec<Relations> {
+        let mut components: Vec<Relations> = (0..self.leaves.len()).map(|i| 1 << i).collect();
+        for (_, rels) in &self.preds {
+            let (merged, rest): (Vec<_>, Vec<_>) =
+                components.into_iter().partition(|c| c & rels != 0);
+            components = rest;
+            components.push(merged.into_iter().fold(0, |a, b| a | b));
+        }
+        components.retain(|c| *c != 0);
+        components.sort_by_key(|c| c.trailing_zeros());
+        components
+    }
+
+    /// Combines disconnected trees by cross joins, from the smallest to the largest.
+    fn cross_join(&self, mut trees: Vec<(Relations, Tree)>) -> Tree {
+        trees.sort_by(|(a, _), (b, _)| self.rows(*a).total_cmp(&self.rows(*b)));
+        let mut trees = trees.into_iter().map(|(_, t)| t);
+        let first = trees.next().expect("empty region");
+        trees.fold(first, |l, r| Tree::Join(Box::new(l), Box::new(r)))
+    }
+}
+
+struct JoinOrder<'a> {
+    expr: &'a RecExpr,
+    /// The egraph containing `expr` for analysis.
+    egraph: EGraph,
+    /// The eclass of each node in `expr`.
+    ids: Vec<Id>,
+    output: RecExpr,
+    /// The node in `output` for each node in `expr`.
+    built: HashMap<Id, Id>,
+}
+
+impl<'a> JoinOrder<'a> {
+    fn new(expr: &'a RecExpr) -> Self {
+        let mut egraph = EGraph::default();
+        let mut ids: Vec<Id> = vec![];
+        for node in expr.as_ref() {
+            let id = egraph.add(node.clone().map_children(|c| ids[usize::from(c)]));
+            ids.push(id);
+        }
+        egraph.rebuild();
+        JoinOrder {
+            expr,
+            egraph,
+            ids,
+            output: RecExpr::default(),
+            built: HashMap::new(),
+        }
+    }
+
+    fn data(&self, id: Id) -> &Data {
+        &self.egraph[self.ids[usize::from(id)]].data
+    }
+
+    /// Copies the node `id` to the output and reorders all regions under it.
+    fn build(&mut self, id: Id) -> Id {
+        if let Some(&new) = self.built.get(&id) {
+            return new;
+        }
+        let new = match self.build_region(id) {
+            Some(new) => new,
+            None => {
+                let node = self.expr[id].clone().map_children(|c| self.build(c));
+                self.output.add(node)
+            }
+        };
+        self.built.insert(id, new);
+        new
+    }
+
+    /// Returns true if `id` is the root of a region.
+    fn is_region(&self, id: Id) -> bool {
+        match &self.expr[id] {
+            Expr::Join([ty, _, _, _]) => self.expr[*ty] == Expr::Inner,
+            Expr::Filter([_, child]) => self.is_region(*child),
+            _ => false,
+        }
+    }
+
+    /// Collects the leaves and predicates of the region rooted at `id`.
+    fn collect(&self, id: Id, leaves: &mut Vec<Id>, preds: &mut Vec<Id>) {
+        match &self.expr[id] {
+            Expr::Join([_, cond, left, right]) if self.is_region(id) => {
+                self.conjuncts(*cond, preds);
+                self.collect(*left, leaves, preds);
+                self.collect(*right, leaves, preds);
+            }
+            Expr::Filter([cond, child]) if self.is_region(id) => {
+                self.conjuncts(*cond, preds);
+                self.collect(*child, leaves, preds);
+            }
+            _ => leaves.push(id),
+        }
+    }
+
+    /// Splits the condition `id` by `and` and appends the non-true conjuncts to `preds`.
+    fn conjuncts(&self, id: Id, preds: &mut Vec<Id>) {
+        match &self.expr[id] {
+            Expr::And([a, b]) => {
+                self.conjuncts(*a, preds);
+                self.conjuncts(*b, preds);
+            }
+            Expr::Constant(Value::Bool(true)) => {}
+            _ => preds.push(id),
+        }
+    }
+
+    /// Returns the region rooted at `id` and the columns of its leaves.
+    ///
+    /// Returns `None` if `id` is not the root of a region or the region is too large.
+    fn region(&self, id: Id) -> Option<(Region, Vec<ColumnSet>)> {
+        if !self.is_region(id) {
+            return None;
+        }
+        let mut leaves = vec![];
+        let mut preds = vec![];
+        self.collect(id, &mut leaves, &mut preds);
+        if leaves.len() > MAX_RELATIONS {
+            return None;
+        }
+        let columns: Vec<ColumnSet> = (leaves.iter())
+            .map(|leaf| self.data(*leaf).columns.clone())
+            .collect();
+        let region = Region {
+            rows: leaves.iter().map(|leaf| self.data(*leaf).rows).collect(),
+            preds: (preds.into_iter())
+                .map(|pred| {
+                    let pred_columns = &self.data(pred).columns;
+                    let rels = (columns.iter().enumerate())
+                        .filter(|(_, c)| !c.is_disjoint(pred_columns))
+                        .fold(0, |rels, (i, _)| rels | (1 << i));
+                    (pred, rels)
+                })
+                .collect(),
+            leaves,
+        };
+        Some((region, columns))
+    }
+
+    /// Returns true if `id` is a `join` and no conjunct of its condition
+    /// references both sides.
+    fn is_cross_product(&self, id: Id) -> bool {
+        let Expr::Join([_, cond, left, right]) = self.expr[id] else {
+            return false;
+        };
+        let mut conds = vec![];
+        self.conjuncts(cond, &mut conds);
+        let (l, r) = (&self.data(left).columns, &self.data(right).columns);
+        !conds.iter().any(|id| {
+            let columns = &self.data(*id).columns;
+            !columns.is_disjoint(l) && !columns.is_disjoint(r)
+        })
+    }
+
+    /// Finds the disconnected regions under `id`.
+    fn find_disconnected(&self, id: Id, visited: &mut HashSet<Id>, output: &mut Vec<Disconnected>) {
+        if !visited.insert(id) {
+            return;
+        }
+        let Some((region, _)) = self.region(id) else {
+            for child in self.expr[id].children() {
+                self.find_disconnected(*child, visited, output);
+            }
+            return;
+        };
+        let components = region.components();
+        if components.len() > 1 {
+            let relation = |i: usize| {
+                let get_node = |id: Id| self.expr[id].clone();
+                get_node(region.leaves[i]).build_recexpr(get_node)
+            };
+            output.push(Disconnected {
+                root: id,
+                components: (components.iter())
+                    .map(|set| {
+                        (0..region.leaves.len())
+                            .filter(|i| set & (1 << i) != 0)
+                            .map(relation)
+                            .collect()
+                    })
+                    .collect(),
+            });
+        }
+        for leaf in &region.leaves {
+            self.find_disconnected(*leaf, visited, output);
+        }
+    }
+
+    /// Reorders the region rooted at `id`.
+    /// Returns `None` if `id` is not the root of a region or the region can not be reordered.
+    fn build_region(&mut self, id: Id) -> Option<Id> {
+        let (region, columns) = self.region(id)?;
+        // the original schema, to restore the order of output columns
+        let extractor = Extractor::new(&self.egraph, AstSize);
+        let mut schema = vec![];
+        for leaf in &region.leaves {
+            for column in self.data(*leaf).schema.as_ref()? {
+                schema.push(extractor.find_best(*column).1);
+            }
+        }
+
+        let tree = match region.leaves.len() <= DP_THRESHOLD {
+            true => region.dp(),
+            false => region.greedy(first_relations(region.leaves.len())),
+        };
+        let mut order = vec![];
+        let (mut plan, _) = self.build_tree(&region, &tree, &columns, &mut order);
+
+        // predicates without any column are applied at the top
+        let residual: Vec<Id> = (region.preds.iter())
+            .filter(|(_, rels)| *rels == 0)
+            .map(|(pred, _)| self.build(*pred))
+            .collect();
+        if let Some(cond) = self.and_all(residual) {
+            plan = self.output.add(Expr::Filter([cond, plan]));
+        }
+        if order.windows(2).any(|w| w[0] > w[1]) {
+            let list = schema.iter().map(|e| self.append(e)).collect();
+            let list = self.output.add(Expr::List(list));
+            plan = self.output.add(Expr::Proj([list, plan]));
+        }
+        Some(plan)
+    }
+
+    /// Builds the join tree to the output.
+    /// Appends the leaves in the output order to `order`.
+    /// Returns the plan and the relations in it.
+    fn build_tree(
+        &mut self,
+        region: &Region,
+        tree: &Tree,
+        columns: &[ColumnSet],
+        order: &mut Vec<usize>,
+    ) -> (Id, Relations) {
+        match tree {
+            Tree::Leaf(i) => {
+                order.push(*i);
+                let mut plan = self.build(region.leaves[*i]);
+                let conds = (region.preds.iter())
+                    .filter(|(_, rels)| *rels == 1 << i)
+                    .map(|(pred, _)| self.build(*pred))
+                    .collect();
+                if let Some(cond) = self.and_all(conds) {
+                    plan = self.output.add(Expr::Filter([cond, plan]));
+                }
+                (plan, 1 << i)
+            }
+            Tree::Join(l, r) => {
+                // the smaller side is on the right to build the hash table
+                let (l, r) = match region.rows(tree_set(l)) < region.rows(tree_set(r)) {
+                    true => (r, l),
+                    false => (l, r),
+                };
+                let (left, l_set) = self.build_tree(region, l, columns, order);
+                let (right, r_set) = self.build_tree(region, r, columns, order);
+                let set = l_set | r_set;
+                let columns_of = |set: Relations| -> ColumnSet {
+                    (columns.iter().enumerate())
+                        .filter(|(i, _)| set & (1 << i) != 0)
+                        .flat_map(|(_, c)| c.iter().cloned())
+                        .collect()
+                };
+                let (l_columns, r_columns) = (columns_of(l_set), columns_of(r_set));
+                let mut keys = vec![];
+                let mut conds = vec![];
+                for (pred, rels) in &region.preds {
+                    if rels & !set != 0 || rels & l_set == 0 || rels & r_set == 0 {
+                        continue;
+                    }
+                    match self.hash_keys(*pred, &l_columns, &r_columns) {
+                        Some(key) => keys.push(key),
+                        None => conds.push(self.build(*pred)),
+                    }
+                }
+                let inner = self.output.add(Expr::Inner);
+                let plan = if keys.is_empty() {
+                    let cond = self
+                        .and_all(conds)
+                        .unwrap_or_else(|| self.output.add(Expr::Constant(Value::Bool(true))));
+                    self.output.add(Expr::Join([inner, cond, left, right]))
+                } else {
+                    let (lkeys, rkeys): (Vec<Id>, Vec<Id>) = keys.into_iter().unzip();
+                    let lkeys = self.output.add(Expr::List(lkeys.into()));
+                    let rkeys = self.output.add(Expr::List(rkeys.into()));
+                    let plan =
+                        (self.output).add(Expr::HashJoin([inner, lkeys, rkeys, left, right]));
+                    match self.and_all(conds) {
+                        Some(cond) => self.output.add(Expr::Filter([cond, plan])),
+                        None => plan,
+                    }
+                };
+                (plan, set)
+            }
+        }
+    }
+
+    /// Returns the left and right key if `pred` is an equality between the two sides.
+    fn hash_keys(
+        &mut self,
+        pred: Id,
+        l_columns: &ColumnSet,
+        r_columns: &ColumnSet,
+    ) -> Option<(Id, Id)> {
+        let Expr::Eq([a, b]) = self.expr[pred] else {
+            return None;
+        };
+        let is_subset = |id: Id, columns: &ColumnSet| {
+            let c = &self.data(id).columns;
+            !c.is_empty() && c.is_subset(columns)
+        };
+        let (l, r) = if is_subset(a, l_columns) && is_subset(b, r_columns) {
+            (a, b)
+        } else if is_subset(b, l_columns) && is_subset(a, r_columns) {
+            (b, a)
+        } else {
+            return None;
+        };
+        Some((self.build(l), self.build(r)))
+    }
+
+    /// Returns the conjunction of `conds`, or `None` if it is empty.
+    fn and_all(&mut self, conds: Vec<Id>) -> Option<Id> {
+        (conds.into_iter()).reduce(|a, b| self.output.add(Expr::And([a, b])))
+    }
+
+    /// Appends `expr` to the output.
+    fn append(&mut self, expr: &RecExpr) -> Id {
+        let mut ids: Vec<Id> = vec![];
+        for node in expr.as_ref() {
+            let id = (self.output).add(node.clone().map_children(|c| ids[usize::from(c)]));
+            ids.push(id);
+        }
+        *ids.last().unwrap()
+    }
+}
+
+/// Returns the relations in the tree.
+fn tree_set(tree: &Tree) -> Relations {
+    match tree {
+        Tree::Leaf(i) => 1 << i,
+        Tree::Join(l, r) => tree_set(l) | tree_set(r),
+    }
+}
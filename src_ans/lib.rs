@@ -1,13 +1,41 @@
+use std::collections::HashMap;
 use std::hash::Hash;
 
 use egg::{define_language, Analysis, DidMerge, Id, Var};
 
+/// Same as [`egg::rewrite!`], but also keeps the source text of each condition on the rule,
+/// which is shown in the [`trace`](trace::TraceStep) of an optimization.
+macro_rules! rw {
+    ($name:expr; $lhs:tt => $rhs:tt $(if $cond:expr)*) => {{
+        let name = $name;
+        egg::rewrite!(name; $lhs => $rhs $(
+            if $crate::trace::Check::new(name, stringify!($cond), $cond)
+        )*)
+    }};
+}
+
 pub mod agg;
+pub mod cost;
+pub mod error;
+pub mod eval;
+pub mod explain;
+pub mod export;
 pub mod expr;
+pub mod fd;
+pub mod join_order;
+pub mod optimizer;
+pub mod order;
+pub mod param;
 pub mod plan;
 pub mod schema;
+pub mod slt;
+pub mod trace;
+pub mod validate;
 mod value;
+pub mod window;
 
+pub use error::Error;
+pub use optimizer::{OptimizeReport, Optimizer, Stage};
 pub use value::*;
 
 pub type RecExpr = egg::RecExpr<Expr>;
@@ -19,10 +47,13 @@ define_language! {
         // values
         Constant(Value),            // null, true, 1, 'hello'
         ColumnIndex(ColumnIndex),   // #0, #1, ...
+        Param(Param),               // $1, $2, ... a placeholder bound by `param::bind`
 
         // utilities
         "`" = Nested(Id),           // (` expr) a wrapper over expr to prevent optimization
         "list" = List(Box<[Id]>),   // (list ...)
+        "as" = Alias([Id; 2]),      // (as expr name) an expression with an alias name
+                                        // only allowed in the select list before planning
 
         // unary operations
         "-" = Neg(Id),
@@ -50,6 +81,17 @@ define_language! {
         "sum" = Sum(Id),
         "avg" = Avg(Id),
         "count" = Count(Id),
+        "rowcount" = RowCount,                  // count(*)
+        "count-distinct" = CountDistinct(Id),
+        "sum-distinct" = SumDistinct(Id),
+        "grouping" = Grouping(Id),              // (grouping key)
+                                                    // 1 if the group key is not in the grouping set of the row, otherwise 0
+
+        // window functions
+        "over" = Over([Id; 3]),                 // (over window_function [partition_key..] [order_key..])
+        "row_number" = RowNumber,
+        "rank" = Rank,
+        "dense_rank" = DenseRank,
 
         // plans
         "scan" = Scan([Id; 2]),                 // (scan table [column..])
@@ -63,6 +105,9 @@ define_language! {
         "topn" = TopN([Id; 4]),                 // (topn limit offset [order_key..] child)
         "join" = Join([Id; 4]),                 // (join join_type expr left right)
         "hashjoin" = HashJoin([Id; 5]),         // (hashjoin join_type [left_expr..] [right_expr..] left right)
+        "mergejoin" = MergeJoin([Id; 5]),       // (mergejoin join_type [left_expr..] [right_expr..] left right)
+                                                    // both children must be sorted by their keys in ascending order
+        "nljoin" = NestedLoopJoin([Id; 4]),     // (nljoin join_type expr left right)
             "inner" = Inner,
             "left_outer" = LeftOuter,
             "right_outer" = RightOuter,
@@ -70,6 +115,25 @@ define_language! {
         "agg" = Agg([Id; 3]),                   // (agg aggs=[expr..] group_keys=[expr..] child)
                                                     // expressions must be agg
                                                     // output = aggs || group_keys
+                                                    // group_keys can also be a grouping-sets
+            "grouping-sets" = GroupingSets(Id),     // (grouping-sets [[expr..]..])
+                                                        // output = all keys in the sets
+                                                        // keys not in a set are filled with null in the rows of that set
+            "rollup" = Rollup(Id),                  // (rollup [expr..])
+            "cube" = Cube(Id),                      // (cube [expr..])
+                                                        // only allowed in group keys before planning
+        "agg-final" = FinalAgg([Id; 3]),        // (agg-final aggs=[expr..] group_keys=[expr..] child)
+                                                    // merges the states of aggs computed by a partial agg in child
+                                                    // output = aggs || group_keys
+        "window" = Window([Id; 2]),             // (window [over..] child)
+                                                    // expressions must be over
+                                                    // output = child || exprs
+        "distinct" = Distinct(Id),              // (distinct child)
+        "union" = Union([Id; 2]),               // (union left right)
+        "union_all" = UnionAll([Id; 2]),        // (union_all left right)
+        "intersect" = Intersect([Id; 2]),       // (intersect left right)
+        "except" = Except([Id; 2]),             // (except left right)
+                                                    // output = left
 
         // internal functions
         "column-merge" = ColumnMerge([Id; 2]),  // (column-merge list1 list2)
@@ -84,33 +148,41 @@ define_language! {
     }
 }
 
-impl Expr {
-    fn as_list(&self) -> &[Id] {
-        match self {
-            Expr::List(l) => l,
-            _ => panic!("expected list"),
-        }
-    }
-}
-
 trait ExprExt {
-    fn as_list(&self) -> &[Id];
+    /// Returns the elements of the list in the eclass, or `None` if the eclass has no list.
+    ///
+    /// It is always a list in a [validated](validate::validate) plan,
+    /// but rules may also run on unvalidated input and should not apply then.
+    fn as_list(&self) -> Option<&[Id]>;
 }
 
 impl<D> ExprExt for egg::EClass<Expr, D> {
-    fn as_list(&self) -> &[Id] {
-        self.iter()
-            .find_map(|e| match e {
-                Expr::List(list) => Some(list),
-                _ => None,
-            })
-            .expect("not list")
+    fn as_list(&self) -> Option<&[Id]> {
+        self.iter().find_map(|e| match e {
+            Expr::List(list) => Some(list.as_ref()),
+            _ => None,
+        })
     }
 }
 
 /// The unified analysis for all rules.
-#[derive(Default)]
-pub struct ExprAnalysis;
+#[derive(Default, Clone)]
+pub struct ExprAnalysis {
+    /// The primary key columns of tables in the catalog.
+    pub primary_keys: HashMap<Column, Vec<Column>>,
+
+    /// True if the join graph of every region of inner joins in the plan is connected,
+    /// so join reordering never introduces a Cartesian product.
+    ///
+    /// It is set by the [`Optimizer`] with [`join_order::disconnected_relations`].
+    /// Otherwise a rotation may keep a Cartesian product that was in the original tree.
+    pub connected_joins: bool,
+
+    /// The conditions that passed for each rule, which are shown in the trace.
+    ///
+    /// They are recorded only if explanations are enabled in the egraph.
+    pub conditions: trace::Conditions,
+}
 
 /// The analysis data associated with each eclass.
 ///
@@ -126,11 +198,23 @@ pub struct Data {
     /// All aggragations in the tree.
     pub aggs: agg::AggSet,
 
+    /// All window functions in the tree.
+    pub windows: window::WindowSet,
+
     /// The schema for plan node: a list of expressions.
     ///
     /// For non-plan node, it is always None.
     /// For plan node, it may be None if the schema is unknown due to unresolved `prune`.
     pub schema: schema::Schema,
+
+    /// The estimated number of rows for plan node.
+    pub rows: cost::Rows,
+
+    /// The order keys that the output rows of plan node are sorted by.
+    pub ordering: order::Ordering,
+
+    /// The functional dependencies and unique keys of plan node.
+    pub fds: fd::FuncDeps,
 }
 
 impl Analysis<Expr> for ExprAnalysis {
@@ -142,7 +226,11 @@ impl Analysis<Expr> for ExprAnalysis {
             constant: expr::eval_constant(egraph, enode),
             columns: plan::analyze_columns(egraph, enode),
             aggs: agg::analyze_aggs(egraph, enode),
+            windows: window::analyze_windows(egraph, enode),
             schema: schema::analyze_schema(egraph, enode),
+            rows: cost::analyze_rows(egraph, enode),
+            ordering: order::analyze_ordering(egraph, enode),
+            fds: fd::analyze_fds(egraph, enode),
         }
     }
 
@@ -158,8 +246,19 @@ impl Analysis<Expr> for ExprAnalysis {
         let merge_const = egg::merge_max(&mut to.constant, from.constant);
         let merge_columns = plan::merge(&mut to.columns, from.columns);
         let merge_aggs = egg::merge_max(&mut to.aggs, from.aggs);
+        let merge_windows = egg::merge_max(&mut to.windows, from.windows);
         let merge_schema = egg::merge_max(&mut to.schema, from.schema);
-        merge_const | merge_columns | merge_aggs | merge_schema
+        let merge_rows = cost::merge(&mut to.rows, from.rows);
+        let merge_ordering = order::merge(&mut to.ordering, from.ordering);
+        let merge_fds = fd::merge(&mut to.fds, from.fds);
+        merge_const
+            | merge_columns
+            | merge_aggs
+            | merge_windows
+            | merge_schema
+            | merge_rows
+            | merge_ordering
+            | merge_fds
     }
 
     /// Modify the graph after analyzing a node.
@@ -0,0 +1,91 @@
+//! Analyze the order of rows produced by plan nodes.
+
+use egg::Subst;
+
+use super::*;
+
+/// The data type of ordering analysis.
+///
+/// A list of order keys, e.g. `(asc a)`, `(desc b)`, that the output rows are sorted by.
+/// It is empty if the output is not known to be sorted.
+pub type Ordering = Vec<Id>;
+
+/// Returns the order of rows produced by the node.
+pub fn analyze_ordering(egraph: &EGraph, enode: &Expr) -> Ordering {
+    use Expr::*;
+    let x = |i: &Id| egraph[*i].data.ordering.clone();
+    match enode {
+        Order([keys, _]) | TopN([_, _, keys, _]) => list(egraph, *keys),
+        // rows are not reordered
+        Filter([_, c]) | Limit([_, _, c]) => x(c),
+        // the order keys must be available after projection
+        Proj([exprs, c]) => {
+            let columns = &egraph[*exprs].data.columns;
+            (x(c).into_iter())
+                .take_while(|key| egraph[*key].data.columns.is_subset(columns))
+                .collect()
+        }
+        // rows from the left side are produced in order,
+        // unless unmatched rows from the right side are appended
+        Join([ty, _, l, _])
+        | HashJoin([ty, _, _, l, _])
+        | MergeJoin([ty, _, _, l, _])
+        | NestedLoopJoin([ty, _, l, _]) => {
+            let ty = &egraph[*ty].nodes;
+            if ty.contains(&Inner) || ty.contains(&LeftOuter) {
+                x(l)
+            } else {
+                vec![]
+            }
+        }
+        _ => vec![],
+    }
+}
+
+/// Merge two orderings and keep their common prefix,
+/// since any node in the eclass may be chosen.
+///
+/// Keys are compared without canonicalization, which may only make the result shorter.
+pub fn merge(to: &mut Ordering, from: Ordering) -> DidMerge {
+    let common = (to.iter().zip(from.iter()))
+        .take_while(|(a, b)| a == b)
+        .count();
+    let did_merge = DidMerge(common < to.len(), common < from.len());
+    to.truncate(common);
+    did_merge
+}
+
+/// Returns the elements of the list node in the eclass.
+fn list(egraph: &EGraph, id: Id) -> Vec<Id> {
+    (egraph[id].iter())
+        .find_map(|e| match e {
+            Expr::List(list) => Some(list.to_vec()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Returns true if the rows of `plan` are sorted by the order keys in `keys`.
+pub fn is_sorted_by(plan: &str, keys: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
+    let plan = var(plan);
+    let keys = var(keys);
+    move |egraph, _, subst| {
+        let keys = list(egraph, subst[keys]);
+        let ordering = &egraph[subst[plan]].data.ordering;
+        keys.len() <= ordering.len()
+            && (keys.iter().zip(ordering)).all(|(a, b)| egraph.find(*a) == egraph.find(*b))
+    }
+}
+
+/// Returns true if the rows of `plan` are sorted by the expression `key` in ascending order first.
+pub fn is_sorted_by_asc(plan: &str, key: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
+    let plan = var(plan);
+    let key = var(key);
+    move |egraph, _, subst| {
+        let Some(asc) = egraph.lookup(Expr::Asc(subst[key])) else {
+            return false;
+        };
+        let ordering = &egraph[subst[plan]].data.ordering;
+        ordering.first().is_some_and(|k| egraph.find(*k) == asc)
+    }
+}
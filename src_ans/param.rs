@@ -0,0 +1,101 @@
+//! Parameterized queries.
+//!
+//! A plan of a prepared statement contains placeholders `$1`, `$2`, ... instead of constants.
+//! Since a placeholder is not a constant, rules that depend on its value do not apply,
+//! and the plan can be optimized once and executed with different parameters:
+//!
+//! ```text
+//! (filter (= a (+ $1 1)) (scan t (list a b)))
+//!
+//! bind [1] => (filter (= a 2) (scan t (list a b)))
+//! ```
+
+use egg::Language;
+
+use super::*;
+
+/// An error when binding parameters.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    // #[error("no value for parameter {0}")]
+    NotBound(Param),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotBound(p) => write!(f, "no value for parameter {p}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Returns the distinct parameters in the plan in ascending order.
+pub fn params(plan: &RecExpr) -> Vec<Param> {
+    let mut params: Vec<Param> = (plan.as_ref().iter())
+        .filter_map(|node| match node {
+            Expr::Param(p) => Some(*p),
+            _ => None,
+        })
+        .collect();
+    params.sort();
+    params.dedup();
+    params
+}
+
+/// Substitutes `values` for the parameters in the plan, where `$1` is `values[0]`,
+/// and folds the expressions that become constants.
+///
+/// The plan is usually optimized before binding, so other nodes are left unchanged.
+pub fn bind(plan: &RecExpr, values: &[Value]) -> Result<RecExpr, Error> {
+    // add the nodes to an egraph to fold constants
+    let mut egraph = EGraph::default();
+    let mut output = RecExpr::default();
+    let mut ids: Vec<(Id, Id)> = Vec::with_capacity(plan.as_ref().len());
+    for node in plan.as_ref() {
+        let node = match node {
+            Expr::Param(p) => (p.0.checked_sub(1))
+                .and_then(|i| values.get(i as usize))
+                .map(|v| Expr::Constant(v.clone()))
+                .ok_or(Error::NotBound(*p))?,
+            _ => node.clone(),
+        };
+        let id = egraph.add(node.clone().map_children(|i| ids[usize::from(i)].1));
+        let node = match &egraph[id].data.constant {
+            Some(v) if is_foldable(&node) => Expr::Constant(v.clone()),
+            _ => node.map_children(|i| ids[usize::from(i)].0),
+        };
+        ids.push((output.add(node), id));
+    }
+    if output.as_ref().is_empty() {
+        return Ok(output);
+    }
+    // remove the children of folded expressions
+    let root = &output[Id::from(output.as_ref().len() - 1)];
+    Ok(root.build_recexpr(|id| output[id].clone()))
+}
+
+/// Returns true if the node is an operation on values, which can be replaced by its result.
+fn is_foldable(node: &Expr) -> bool {
+    use Expr::*;
+    matches!(
+        node,
+        Neg(_)
+            | Not(_)
+            | IsNull(_)
+            | Add(_)
+            | Sub(_)
+            | Mul(_)
+            | Div(_)
+            | Eq(_)
+            | NotEq(_)
+            | Gt(_)
+            | Lt(_)
+            | GtEq(_)
+            | LtEq(_)
+            | And(_)
+            | Or(_)
+            | Xor(_)
+    )
+}
@@ -1,11 +1,14 @@
 //! Plan optimization rules.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+use crate::fd::has_unique_key;
+use crate::order::{is_sorted_by, is_sorted_by_asc};
 use crate::schema::schema_is_eq;
+use crate::window::is_same_window_spec;
 
 use super::*;
-use egg::{rewrite as rw, Applier, Language, Pattern, PatternAst, Subst, Symbol, Var};
+use egg::{Applier, Language, Pattern, PatternAst, Subst, Symbol, Var};
 
 /// Returns the rules that always improve the plan.
 pub fn rules() -> Vec<Rewrite> {
@@ -15,11 +18,13 @@ pub fn rules() -> Vec<Rewrite> {
     rules.extend(predicate_pushdown_rules());
     rules.extend(projection_pushdown_rules());
     rules.extend(join_rules());
+    rules.extend(join_reorder_rules());
+    rules.extend(agg_pushdown_rules());
     rules
 }
 
 #[rustfmt::skip]
-fn cancel_rules() -> Vec<Rewrite> { vec![
+pub fn cancel_rules() -> Vec<Rewrite> { vec![
     rw!("limit-null";       "(limit null 0 ?child)"     => "?child"),
     rw!("limit-0";          "(limit 0 ?offset ?child)"  => "(empty ?child)"),
     rw!("order-null";       "(order (list) ?child)"     => "?child"),
@@ -34,10 +39,39 @@ fn cancel_rules() -> Vec<Rewrite> { vec![
     rw!("topn-on-empty";    "(topn ?limit ?offset ?keys (empty ?c))"    => "(empty ?c)"),
     rw!("inner-join-on-left-empty";  "(join inner ?on (empty ?l) ?r)"   => "(empty (join inner false ?l ?r))"),
     rw!("inner-join-on-right-empty"; "(join inner ?on ?l (empty ?r))"   => "(empty (join inner false ?l ?r))"),
+
+    rw!("order-on-sorted";
+        "(order ?keys ?child)" => "?child"
+        if is_sorted_by("?child", "?keys")
+    ),
+    rw!("topn-on-sorted";
+        "(topn ?limit ?offset ?keys ?child)" => "(limit ?limit ?offset ?child)"
+        if is_sorted_by("?child", "?keys")
+    ),
+
+    rw!("distinct-on-empty";            "(distinct (empty ?c))"         => "(empty ?c)"),
+    rw!("union-all-on-right-empty";     "(union_all ?l (empty ?r))"     => "?l"),
+    rw!("union-all-on-left-empty";      "(union_all (empty ?l) ?r)"     => "?r" if schema_is_eq("?l", "?r")),
+    rw!("union-on-right-empty";         "(union ?l (empty ?r))"         => "(distinct ?l)"),
+    rw!("intersect-on-left-empty";      "(intersect (empty ?l) ?r)"     => "(empty ?l)"),
+    rw!("intersect-on-right-empty";     "(intersect ?l (empty ?r))"     => "(empty ?l)"),
+    rw!("except-on-left-empty";         "(except (empty ?l) ?r)"        => "(empty ?l)"),
+    rw!("except-on-right-empty";        "(except ?l (empty ?r))"        => "(distinct ?l)"),
+
+    rw!("distinct-on-unique";
+        "(distinct ?child)" => "?child"
+        if has_unique_key("?child")
+    ),
+    // the output of aggregation is already distinct on group keys,
+    // unless there are grouping sets whose null keys may be the same as values
+    rw!("distinct-on-agg";
+        "(distinct (agg ?aggs ?groupby ?child))" => "(agg ?aggs ?groupby ?child)"
+        if is_list("?groupby")
+    ),
 ]}
 
 #[rustfmt::skip]
-fn merge_rules() -> Vec<Rewrite> { vec![
+pub fn merge_rules() -> Vec<Rewrite> { vec![
     rw!("limit-order-topn";
         "(limit ?limit ?offset (order ?keys ?child))" =>
         "(topn ?limit ?offset ?keys ?child)"
@@ -50,13 +84,46 @@ fn merge_rules() -> Vec<Rewrite> { vec![
         "(proj ?exprs1 (proj ?exprs2 ?child))" =>
         "(proj ?exprs1 ?child)"
     ),
+    rw!("window-merge";
+        "(window ?windows1 (window ?windows2 ?child))" =>
+        { WindowMerge {
+            lists: [var("?windows2"), var("?windows1")],
+            child: var("?child"),
+        }}
+        if is_same_window_spec("?windows1", "?windows2")
+    ),
+    rw!("union-to-distinct";
+        "(union ?left ?right)" =>
+        "(distinct (union_all ?left ?right))"
+    ),
+    rw!("distinct-to-agg";
+        "(distinct ?child)" =>
+        { DistinctToAgg { child: var("?child") } }
+    ),
 ]}
 
 #[rustfmt::skip]
-fn predicate_pushdown_rules() -> Vec<Rewrite> { vec![
+pub fn predicate_pushdown_rules() -> Vec<Rewrite> { vec![
     pushdown("filter", "?cond", "order", "?keys"),
     pushdown("filter", "?cond", "limit", "?limit ?offset"),
     pushdown("filter", "?cond", "topn", "?limit ?offset ?keys"),
+    pushdown("filter", "?cond", "distinct", ""),
+    rw!("pushdown-filter-union";
+        "(filter ?cond (union ?left ?right))" =>
+        { PushdownSetOp { arg: var("?cond"), plan: |cond, child| Expr::Filter([cond, child]), set_op: Expr::Union } }
+    ),
+    rw!("pushdown-filter-union-all";
+        "(filter ?cond (union_all ?left ?right))" =>
+        { PushdownSetOp { arg: var("?cond"), plan: |cond, child| Expr::Filter([cond, child]), set_op: Expr::UnionAll } }
+    ),
+    rw!("pushdown-filter-intersect";
+        "(filter ?cond (intersect ?left ?right))" =>
+        { PushdownSetOp { arg: var("?cond"), plan: |cond, child| Expr::Filter([cond, child]), set_op: Expr::Intersect } }
+    ),
+    rw!("pushdown-filter-except";
+        "(filter ?cond (except ?left ?right))" =>
+        { PushdownSetOp { arg: var("?cond"), plan: |cond, child| Expr::Filter([cond, child]), set_op: Expr::Except } }
+    ),
     rw!("pushdown-filter-join";
         "(filter ?cond (join inner ?on ?left ?right))" =>
         "(join inner (and ?on ?cond) ?left ?right)"
@@ -95,16 +162,156 @@ fn pushdown(a: &str, a_args: &str, b: &str, b_args: &str) -> Rewrite {
     Rewrite::new(name, searcher, applier).unwrap()
 }
 
+/// Pushdown a plan with an expression argument, i.e. `filter` or `proj`,
+/// to both children of a set operation.
+///
+/// The columns of the right child are matched with those of the left child by position,
+/// so the argument is rewritten with the right columns before being pushed to the right.
+struct PushdownSetOp {
+    /// The expression argument of the plan.
+    arg: Var,
+    /// Returns the plan over a child.
+    plan: fn(Id, Id) -> Expr,
+    /// Returns the set operation over the children.
+    set_op: fn([Id; 2]) -> Expr,
+}
+
+impl Applier<Expr, ExprAnalysis> for PushdownSetOp {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let [arg, left, right] = [self.arg, var("?left"), var("?right")].map(|v| subst[v]);
+        let (Some(lschema), Some(rschema)) = (
+            egraph[left].data.schema.clone(),
+            egraph[right].data.schema.clone(),
+        ) else {
+            return vec![];
+        };
+        if lschema.len() != rschema.len() {
+            return vec![];
+        }
+        let map = (lschema.iter().map(|id| egraph.find(*id)))
+            .zip(rschema.iter().cloned())
+            .collect();
+        let Some(rarg) = rename(egraph, arg, &map, &mut HashMap::new()) else {
+            return vec![];
+        };
+        let left = egraph.add((self.plan)(arg, left));
+        let right = egraph.add((self.plan)(rarg, right));
+        let id = egraph.add((self.set_op)([left, right]));
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Returns the expression `id` with the subexpressions in the keys of `map` replaced by
+/// their values. Returns `None` if no node in the eclass can be rewritten without a cycle.
+fn rename(
+    egraph: &mut EGraph,
+    id: Id,
+    map: &HashMap<Id, Id>,
+    memo: &mut HashMap<Id, Option<Id>>,
+) -> Option<Id> {
+    let id = egraph.find(id);
+    if let Some(to) = map.get(&id) {
+        return Some(*to);
+    }
+    if let Some(renamed) = memo.get(&id) {
+        return *renamed;
+    }
+    // mark as visiting to break cycles
+    memo.insert(id, None);
+    let nodes = egraph[id].nodes.clone();
+    let renamed = nodes.into_iter().find_map(|node| {
+        let mut children = vec![];
+        for child in node.children() {
+            children.push(rename(egraph, *child, map, memo)?);
+        }
+        let mut children = children.into_iter();
+        let node = node.map_children(|_| children.next().unwrap());
+        Some(egraph.add(node))
+    });
+    memo.insert(id, renamed);
+    renamed
+}
+
+/// Merges two windows into one which computes the functions of both lists in order.
+struct WindowMerge {
+    lists: [Var; 2],
+    child: Var,
+}
+
+impl Applier<Expr, ExprAnalysis> for WindowMerge {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let mut list = vec![];
+        for v in self.lists.iter() {
+            let Some(windows) = egraph[subst[*v]].as_list() else {
+                return vec![];
+            };
+            list.extend_from_slice(windows);
+        }
+        let list = egraph.add(Expr::List(list.into()));
+        let id = egraph.add(Expr::Window([list, subst[self.child]]));
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Converts `distinct` to an `agg` grouping by all columns of the child.
+struct DistinctToAgg {
+    child: Var,
+}
+
+impl Applier<Expr, ExprAnalysis> for DistinctToAgg {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let child = subst[self.child];
+        let Some(schema) = egraph[child].data.schema.clone() else {
+            return vec![];
+        };
+        let aggs = egraph.add(Expr::List([].into()));
+        let groupby = egraph.add(Expr::List(schema.into()));
+        let id = egraph.add(Expr::Agg([aggs, groupby, child]));
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
 #[rustfmt::skip]
 pub fn join_rules() -> Vec<Rewrite> { vec![
-    // we only have right rotation rule,
-    // because the initial state is always a left-deep tree
-    // thus left rotation is not needed.
-    rw!("join-reorder";
-        "(join ?type ?cond2 (join ?type ?cond1 ?left ?mid) ?right)" =>
-        "(join ?type ?cond1 ?left (join ?type ?cond2 ?mid ?right))"
-        if columns_is_disjoint("?cond2", "?left")
-    ),
     rw!("hash-join-on-one-eq";
         "(join ?type (= ?el ?er) ?left ?right)" =>
         "(hashjoin ?type (list ?el) (list ?er) ?left ?right)"
@@ -120,18 +327,340 @@ pub fn join_rules() -> Vec<Rewrite> { vec![
         if columns_is_subset("?r2", "?right")
     ),
     // TODO: support more than two equals
+    rw!("merge-join-on-sorted";
+        "(join ?type (= ?el ?er) ?left ?right)" =>
+        "(mergejoin ?type (list ?el) (list ?er) ?left ?right)"
+        if columns_is_subset("?el", "?left")
+        if columns_is_subset("?er", "?right")
+        if is_sorted_by_asc("?left", "?el")
+        if is_sorted_by_asc("?right", "?er")
+    ),
+    // sorting the inputs of a join is only allowed under an order,
+    // otherwise the join would be in the same eclass with a sorted plan.
+    rw!("order-merge-join";
+        "(order (list (asc ?el)) (join inner (= ?el ?er) ?left ?right))" =>
+        "(mergejoin inner (list ?el) (list ?er)
+            (order (list (asc ?el)) ?left)
+            (order (list (asc ?er)) ?right)
+        )"
+        if columns_is_subset("?el", "?left")
+        if columns_is_subset("?er", "?right")
+    ),
+    rw!("nested-loop-join";
+        "(join ?type ?cond ?left ?right)" =>
+        "(nljoin ?type ?cond ?left ?right)"
+    ),
+    // choose the build side of hash join.
+    // swapping changes the order of output columns,
+    // so it is only allowed under a projection.
+    rw!("hash-join-swap";
+        "(proj ?exprs (hashjoin inner ?lkeys ?rkeys ?left ?right))" =>
+        "(proj ?exprs (hashjoin inner ?rkeys ?lkeys ?right ?left))"
+    ),
 ]}
 
+/// Reorder inner joins.
+///
+/// Rotations in both directions together with swapping the children of a join
+/// reach all join orders. Since the rules do not decide which order is better,
+/// the plan should be extracted with [`CostFn`](crate::cost::CostFn).
+#[rustfmt::skip]
+pub fn join_reorder_rules() -> Vec<Rewrite> { vec![
+    rw!("join-rotate-right";
+        "(join inner ?cond2 (join inner ?cond1 ?left ?mid) ?right)" =>
+        { JoinRotate { to_right: true } }
+    ),
+    rw!("join-rotate-left";
+        "(join inner ?cond1 ?left (join inner ?cond2 ?mid ?right))" =>
+        { JoinRotate { to_right: false } }
+    ),
+    // swapping changes the order of output columns,
+    // so it is only allowed under a projection.
+    rw!("join-swap";
+        "(proj ?exprs (join inner ?cond ?left ?right))" =>
+        "(proj ?exprs (join inner ?cond ?right ?left))"
+    ),
+]}
+
+/// Rotate a tree of two inner joins and redistribute their conditions.
+///
+/// A conjunct of the conditions is placed on the lower join if all of its columns
+/// are provided by the children of the lower join. Otherwise it goes to the upper one.
+///
+/// The rotation is not applied if it introduces a Cartesian product, unless there was one
+/// in the original tree and the query needs one, see [`ExprAnalysis::connected_joins`].
+/// A plan from the binder is a chain of Cartesian products under a filter,
+/// so the original tree alone does not tell whether the query needs one.
+struct JoinRotate {
+    /// Rotate `(join (join left mid) right)` to `(join left (join mid right))` if true,
+    /// or the opposite if false.
+    to_right: bool,
+}
+
+impl Applier<Expr, ExprAnalysis> for JoinRotate {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let [left, mid, right] = ["?left", "?mid", "?right"].map(|v| subst[var(v)]);
+        let mut visited = HashSet::new();
+        let [conds1, conds2] = ["?cond1", "?cond2"].map(|v| {
+            let mut conds = vec![];
+            conjuncts(egraph, subst[var(v)], &mut conds, &mut visited);
+            conds
+        });
+        let columns = |ids: &[Id]| -> ColumnSet {
+            (ids.iter())
+                .flat_map(|id| egraph[*id].data.columns.iter().cloned())
+                .collect()
+        };
+        let [l, m, r] = [left, mid, right].map(|id| columns(&[id]));
+        let (lm, mr) = (columns(&[left, mid]), columns(&[mid, right]));
+
+        let (lower_left, lower_right) = match self.to_right {
+            true => (mid, right),
+            false => (left, mid),
+        };
+        let lower_columns = match self.to_right {
+            true => &mr,
+            false => &lm,
+        };
+        let (lower_conds, upper_conds): (Vec<Id>, Vec<Id>) = (conds1.iter())
+            .chain(conds2.iter())
+            .partition(|id| egraph[**id].data.columns.is_subset(lower_columns));
+
+        // do not introduce cross products unless there was one and the query needs one
+        let was_cross_product = !egraph.analysis.connected_joins
+            && match self.to_right {
+                true => {
+                    is_cross_product(egraph, &conds1, &l, &m)
+                        || is_cross_product(egraph, &conds2, &lm, &r)
+                }
+                false => {
+                    is_cross_product(egraph, &conds1, &l, &mr)
+                        || is_cross_product(egraph, &conds2, &m, &r)
+                }
+            };
+        let is_cross_product = match self.to_right {
+            true => {
+                is_cross_product(egraph, &lower_conds, &m, &r)
+                    || is_cross_product(egraph, &upper_conds, &l, &mr)
+            }
+            false => {
+                is_cross_product(egraph, &lower_conds, &l, &m)
+                    || is_cross_product(egraph, &upper_conds, &lm, &r)
+            }
+        };
+        if is_cross_product && !was_cross_product {
+            return vec![];
+        }
+
+        let inner = egraph.add(Expr::Inner);
+        let lower_cond = and_all(egraph, lower_conds);
+        let upper_cond = and_all(egraph, upper_conds);
+        let lower = egraph.add(Expr::Join([inner, lower_cond, lower_left, lower_right]));
+        let id = match self.to_right {
+            true => egraph.add(Expr::Join([inner, upper_cond, left, lower])),
+            false => egraph.add(Expr::Join([inner, upper_cond, lower, right])),
+        };
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Pushdown partial aggregations below inner joins.
+///
+/// The aggregation is split into a partial one below the join and a final one above it,
+/// which reduces the rows to be joined. Since it is not always beneficial,
+/// the plan should be extracted with [`CostFn`](crate::cost::CostFn).
+#[rustfmt::skip]
+pub fn agg_pushdown_rules() -> Vec<Rewrite> { vec![
+    rw!("pushdown-agg-join-left";
+        "(agg ?aggs ?groupby (join inner ?cond ?left ?right))" =>
+        { EagerAgg { to_left: true } }
+        if is_list("?groupby")
+    ),
+    rw!("pushdown-agg-join-right";
+        "(agg ?aggs ?groupby (join inner ?cond ?left ?right))" =>
+        { EagerAgg { to_left: false } }
+        if is_list("?groupby")
+    ),
+]}
+
+/// Split an aggregation over an inner join into a final aggregation above the join
+/// and a partial aggregation on one side of it.
+///
+/// It is applied only if all aggregations can be split and their arguments come from
+/// that side, every group key comes from either side, and the join keys from that side
+/// are included in the group keys.
+struct EagerAgg {
+    /// Pushdown to the left side if true, or the right side if false.
+    to_left: bool,
+}
+
+impl Applier<Expr, ExprAnalysis> for EagerAgg {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let [aggs, groupby, cond, left, right] =
+            ["?aggs", "?groupby", "?cond", "?left", "?right"].map(|v| subst[var(v)]);
+        let (side, other) = match self.to_left {
+            true => (left, right),
+            false => (right, left),
+        };
+        let side_columns = egraph[side].data.columns.clone();
+        let other_columns = &egraph[other].data.columns;
+        if !egraph[aggs].data.columns.is_subset(&side_columns) {
+            return vec![];
+        }
+        let Some(keys) = egraph[groupby].as_list() else {
+            return vec![];
+        };
+        let mut side_keys = vec![];
+        for key in keys {
+            let columns = &egraph[*key].data.columns;
+            if columns.is_subset(&side_columns) {
+                side_keys.push(egraph.find(*key));
+            } else if !columns.is_subset(other_columns) {
+                return vec![];
+            }
+        }
+        // the join keys must be kept after aggregation
+        let join_keys_in_groupby =
+            (egraph[cond].data.columns.intersection(&side_columns)).all(|col| {
+                egraph
+                    .lookup(Expr::Column(*col))
+                    .is_some_and(|id| side_keys.contains(&id))
+            });
+        if !join_keys_in_groupby {
+            return vec![];
+        }
+        let Some(states) = agg::add_agg_states(egraph, aggs) else {
+            return vec![];
+        };
+        let side_keys = egraph.add(Expr::List(side_keys.into()));
+        let partial = egraph.add(Expr::Agg([states, side_keys, side]));
+        let inner = egraph.add(Expr::Inner);
+        let join = match self.to_left {
+            true => egraph.add(Expr::Join([inner, cond, partial, right])),
+            false => egraph.add(Expr::Join([inner, cond, left, partial])),
+        };
+        let id = egraph.add(Expr::FinalAgg([aggs, groupby, join]));
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Split aggregations into two phases for parallel or distributed execution.
+///
+/// The partial aggregation computes the states of aggregations on each partition of rows,
+/// and the final one merges them. Since the cost model does not consider parallelism,
+/// these rules are not included in [`rules`].
+#[rustfmt::skip]
+pub fn two_phase_agg_rules() -> Vec<Rewrite> { vec![
+    rw!("split-agg";
+        "(agg ?aggs ?groupby ?child)" =>
+        { SplitAgg }
+        if is_list("?groupby")
+    ),
+]}
+
+/// Split `(agg aggs keys child)` into `(agg-final aggs keys (agg states keys child))`.
+///
+/// Splitting the partial aggregation again gives the same states,
+/// so the rule only applies once for each aggregation.
+struct SplitAgg;
+
+impl Applier<Expr, ExprAnalysis> for SplitAgg {
+    fn apply_one(
+        &self,
+        egraph: &mut EGraph,
+        eclass: Id,
+        subst: &Subst,
+        _searcher_ast: Option<&PatternAst<Expr>>,
+        rule_name: Symbol,
+    ) -> Vec<Id> {
+        let [aggs, groupby, child] = ["?aggs", "?groupby", "?child"].map(|v| subst[var(v)]);
+        let Some(states) = agg::add_agg_states(egraph, aggs) else {
+            return vec![];
+        };
+        let partial = egraph.add(Expr::Agg([states, groupby, child]));
+        let id = egraph.add(Expr::FinalAgg([aggs, groupby, partial]));
+
+        // copied from `Pattern::apply_one`
+        if egraph.union_trusted(eclass, id, rule_name) {
+            vec![eclass]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Returns true if no condition references columns from both sides,
+/// i.e. the join is a Cartesian product.
+fn is_cross_product(egraph: &EGraph, conds: &[Id], left: &ColumnSet, right: &ColumnSet) -> bool {
+    !conds.iter().any(|id| {
+        let columns = &egraph[*id].data.columns;
+        !columns.is_disjoint(left) && !columns.is_disjoint(right)
+    })
+}
+
+/// Splits the condition `id` by `and` and appends the non-true conjuncts to `conds`.
+///
+/// The egraph may not be rebuilt while applying rules, so ids are canonicalized before
+/// comparison. Otherwise `(and a a)` in the eclass of `a` would drop `a`.
+fn conjuncts(egraph: &EGraph, id: Id, conds: &mut Vec<Id>, visited: &mut HashSet<Id>) {
+    let id = egraph.find(id);
+    if !visited.insert(id) || egraph[id].data.constant == Some(Value::Bool(true)) {
+        return;
+    }
+    let and = egraph[id].iter().find_map(|e| match e {
+        Expr::And([a, b]) => {
+            let [a, b] = [egraph.find(*a), egraph.find(*b)];
+            (a != id && b != id).then_some([a, b])
+        }
+        _ => None,
+    });
+    match and {
+        Some([a, b]) => {
+            conjuncts(egraph, a, conds, visited);
+            conjuncts(egraph, b, conds, visited);
+        }
+        None => conds.push(id),
+    }
+}
+
+/// Returns the conjunction of `conds`, or `true` if it is empty.
+fn and_all(egraph: &mut EGraph, conds: Vec<Id>) -> Id {
+    (conds.into_iter())
+        .reduce(|a, b| egraph.add(Expr::And([a, b])))
+        .unwrap_or_else(|| egraph.add(Expr::Constant(Value::Bool(true))))
+}
+
 /// Returns true if the columns in `var1` are a subset of the columns in `var2`.
 fn columns_is_subset(var1: &str, var2: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
     columns_is(var1, var2, ColumnSet::is_subset)
 }
 
-/// Returns true if the columns in `var1` has no elements in common with the columns in `var2`.
-fn columns_is_disjoint(var1: &str, var2: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
-    columns_is(var1, var2, ColumnSet::is_disjoint)
-}
-
 fn columns_is(
     var1: &str,
     var2: &str,
@@ -156,7 +685,9 @@ pub fn analyze_columns(egraph: &EGraph, enode: &Expr) -> ColumnSet {
     match enode {
         Column(col) => [*col].into_iter().collect(),
         Proj([exprs, _]) => x(exprs).clone(),
-        Agg([exprs, group_keys, _]) => x(exprs).union(x(group_keys)).cloned().collect(),
+        Agg([exprs, group_keys, _]) | FinalAgg([exprs, group_keys, _]) => {
+            x(exprs).union(x(group_keys)).cloned().collect()
+        }
         ColumnPrune([filter, _]) => x(filter).clone(), // inaccurate
         _ => {
             // merge the columns from all children
@@ -202,6 +733,19 @@ pub fn projection_pushdown_rules() -> Vec<Rewrite> { vec![
         "(agg ?aggs ?groupby ?child)" =>
         "(agg ?aggs ?groupby (proj (column-merge ?aggs ?groupby) ?child))"
     ),
+    // only bag semantics preserves the rows after projection
+    rw!("pushdown-proj-union-all";
+        "(proj ?exprs (union_all ?left ?right))" =>
+        { PushdownSetOp {
+            arg: var("?exprs"),
+            plan: |exprs, child| Expr::Proj([exprs, child]),
+            set_op: Expr::UnionAll,
+        }}
+    ),
+    rw!("pushdown-proj-window";
+        "(proj ?exprs (window ?windows ?child))" =>
+        "(proj ?exprs (window ?windows (proj (column-merge ?exprs ?windows) ?child)))"
+    ),
     rw!("pushdown-proj-join";
         "(proj ?exprs (join ?type ?on ?left ?right))" =>
         "(proj ?exprs (join ?type ?on
@@ -209,6 +753,27 @@ pub fn projection_pushdown_rules() -> Vec<Rewrite> { vec![
             (proj (column-prune ?right (column-merge ?exprs ?on)) ?right)
         ))"
     ),
+    rw!("pushdown-proj-hashjoin";
+        "(proj ?exprs (hashjoin ?type ?lkeys ?rkeys ?left ?right))" =>
+        "(proj ?exprs (hashjoin ?type ?lkeys ?rkeys
+            (proj (column-prune ?left (column-merge ?exprs ?lkeys)) ?left)
+            (proj (column-prune ?right (column-merge ?exprs ?rkeys)) ?right)
+        ))"
+    ),
+    rw!("pushdown-proj-mergejoin";
+        "(proj ?exprs (mergejoin ?type ?lkeys ?rkeys ?left ?right))" =>
+        "(proj ?exprs (mergejoin ?type ?lkeys ?rkeys
+            (proj (column-prune ?left (column-merge ?exprs ?lkeys)) ?left)
+            (proj (column-prune ?right (column-merge ?exprs ?rkeys)) ?right)
+        ))"
+    ),
+    rw!("pushdown-proj-nljoin";
+        "(proj ?exprs (nljoin ?type ?on ?left ?right))" =>
+        "(proj ?exprs (nljoin ?type ?on
+            (proj (column-prune ?left (column-merge ?exprs ?on)) ?left)
+            (proj (column-prune ?right (column-merge ?exprs ?on)) ?right)
+        ))"
+    ),
     // column pruning
     rw!("pushdown-proj-scan";
         "(proj ?exprs (scan ?table ?columns))" =>
@@ -243,20 +808,20 @@ impl Applier<Expr, ExprAnalysis> for ColumnMerge {
         eclass: Id,
         subst: &Subst,
         _searcher_ast: Option<&PatternAst<Expr>>,
-        _rule_name: Symbol,
+        rule_name: Symbol,
     ) -> Vec<Id> {
         let list1 = &egraph[subst[self.lists[0]]].data.columns;
         let list2 = &egraph[subst[self.lists[1]]].data.columns;
-        let mut list: Vec<&Column> = list1.union(list2).collect();
+        let mut list: Vec<Column> = list1.union(list2).cloned().collect();
         list.sort_unstable_by_key(|c| c.as_str());
         let list = list
             .into_iter()
-            .map(|col| egraph.lookup(Expr::Column(col.clone())).unwrap())
+            .map(|col| egraph.add(Expr::Column(col)))
             .collect();
         let id = egraph.add(Expr::List(list));
 
         // copied from `Pattern::apply_one`
-        if egraph.union(eclass, id) {
+        if egraph.union_trusted(eclass, id, rule_name) {
             vec![eclass]
         } else {
             vec![]
@@ -277,17 +842,19 @@ impl Applier<Expr, ExprAnalysis> for ColumnPrune {
         eclass: Id,
         subst: &Subst,
         _searcher_ast: Option<&PatternAst<Expr>>,
-        _rule_name: Symbol,
+        rule_name: Symbol,
     ) -> Vec<Id> {
         let columns = &egraph[subst[self.filter]].data.columns;
-        let list = egraph[subst[self.list]].as_list();
+        let Some(list) = egraph[subst[self.list]].as_list() else {
+            return vec![];
+        };
         let pruned = (list.iter().cloned())
             .filter(|id| egraph[*id].data.columns.is_subset(columns))
             .collect();
         let id = egraph.add(Expr::List(pruned));
 
         // copied from `Pattern::apply_one`
-        if egraph.union(eclass, id) {
+        if egraph.union_trusted(eclass, id, rule_name) {
             vec![eclass]
         } else {
             vec![]
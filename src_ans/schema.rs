@@ -13,13 +13,20 @@ pub type Schema = Option<Vec<Id>>;
 pub fn analyze_schema(egraph: &EGraph, enode: &Expr) -> Schema {
     use Expr::*;
     let x = |i: &Id| egraph[*i].data.schema.clone();
-    let concat = |v1: Vec<Id>, v2: Vec<Id>| v1.into_iter().chain(v2.into_iter()).collect();
+    let concat = |v1: Vec<Id>, v2: Vec<Id>| v1.into_iter().chain(v2).collect();
     Some(match enode {
         // equal to child
-        Filter([_, c]) | Order([_, c]) | Limit([_, _, c]) | TopN([_, _, _, c]) | Empty(c) => x(c)?,
+        Filter([_, c]) | Order([_, c]) | Limit([_, _, c]) | TopN([_, _, _, c]) | Empty(c)
+        | Distinct(c) => x(c)?,
+
+        // equal to left child
+        Union([l, _]) | UnionAll([l, _]) | Intersect([l, _]) | Except([l, _]) => x(l)?,
 
         // concat 2 children
-        Join([_, _, l, r]) | HashJoin([_, _, _, l, r]) => concat(x(l)?, x(r)?),
+        Join([_, _, l, r])
+        | HashJoin([_, _, _, l, r])
+        | MergeJoin([_, _, _, l, r])
+        | NestedLoopJoin([_, _, l, r]) => concat(x(l)?, x(r)?),
 
         // list is the source for the following nodes
         List(ids) => ids.to_vec(),
@@ -28,7 +35,22 @@ pub fn analyze_schema(egraph: &EGraph, enode: &Expr) -> Schema {
         Scan([_, columns]) => x(columns)?,
         Values(vs) => vs.first().and_then(x)?,
         Proj([exprs, _]) => x(exprs)?,
-        Agg([exprs, group_keys, _]) => concat(x(exprs)?, x(group_keys)?),
+        // keys not in a grouping set are null
+        GroupingSets(sets) => {
+            let mut keys = vec![];
+            for set in x(sets)? {
+                for key in x(&set)? {
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+            }
+            keys
+        }
+        Agg([exprs, group_keys, _]) | FinalAgg([exprs, group_keys, _]) => {
+            concat(x(exprs)?, x(group_keys)?)
+        }
+        Window([exprs, c]) => concat(x(c)?, x(exprs)?),
 
         // not plan node
         _ => return None,
@@ -0,0 +1,329 @@
+//! Run test scripts of plans in the style of [sqllogictest](https://www.sqlite.org/sqllogictest).
+//!
+//! A script is a sequence of records separated by blank lines.
+//! Lines starting with `#` are comments.
+//!
+//! ```text
+//! # a table with its columns, an optional primary key, and its rows
+//! table t (t.id t.v) key (t.id)
+//! 1 'a'
+//! 2 null
+//!
+//! # a query optimizes the plan and executes it, `rowsort` sorts the rows
+//! query rowsort
+//! (proj (list t.v) (filter (= t.id 1) (scan t (list t.id t.v))))
+//! ---- plan
+//! (proj (list t.v) (filter (= t.id 1) (scan t (list t.id t.v))))
+//! ---- result
+//! 'a'
+//! ```
+//!
+//! A query checks the sections that are present: the optimized plan, the rows it returns,
+//! or the `---- error` raised by the optimizer or the evaluator.
+//! The optimized plan must also return the same rows as the input plan, in any order.
+//!
+//! [`run`] returns the script with the expected sections replaced by the actual output,
+//! which can override the script when the changes are expected.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::*;
+use crate::eval::{Row, Table, Tables};
+
+/// A record in the script that does not pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The line number of the record, starting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The result of running a script.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub failures: Vec<Failure>,
+    /// The script with the actual output of all queries.
+    pub output: String,
+}
+
+/// Runs the script.
+pub fn run(script: &str) -> Outcome {
+    let mut runner = Runner::default();
+    let lines: Vec<&str> = script.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let end = (i..lines.len())
+            .find(|j| lines[*j].trim().is_empty())
+            .unwrap_or(lines.len());
+        let result = if let Some(header) = line.strip_prefix("table ") {
+            runner
+                .output
+                .extend(lines[i..end].iter().map(|l| l.to_string()));
+            runner.table(header, &lines[i + 1..end])
+        } else if line == "query" || line.starts_with("query ") {
+            runner.query(line, &lines[i + 1..end])
+        } else if line.trim().is_empty() || line.starts_with('#') {
+            runner.output.push(line.into());
+            i += 1;
+            continue;
+        } else {
+            runner
+                .output
+                .extend(lines[i..end].iter().map(|l| l.to_string()));
+            Err(format!("unknown record: {line}"))
+        };
+        if let Err(message) = result {
+            runner.failures.push(Failure {
+                line: i + 1,
+                message,
+            });
+        }
+        i = end;
+    }
+    let mut output = runner.output.join("\n");
+    output.push('\n');
+    Outcome {
+        failures: runner.failures,
+        output,
+    }
+}
+
+#[derive(Default)]
+struct Runner {
+    tables: Tables,
+    analysis: ExprAnalysis,
+    failures: Vec<Failure>,
+    output: Vec<String>,
+}
+
+/// The actual output of a query.
+struct Actual {
+    /// The optimized plan.
+    plan: String,
+    /// The rows returned by the optimized plan.
+    rows: Vec<String>,
+    /// The rows returned by the input plan.
+    input_rows: Vec<String>,
+}
+
+impl Runner {
+    /// Adds a table with the header `name (columns..) [key (columns..)]` and the rows.
+    fn table(&mut self, header: &str, rows: &[&str]) -> Result<(), String> {
+        let invalid = || format!("invalid table: {header}");
+        let (name, rest) = header.split_once('(').ok_or_else(invalid)?;
+        let (columns, rest) = rest.split_once(')').ok_or_else(invalid)?;
+        let name = name.trim();
+        let columns: Vec<Column> = columns.split_whitespace().map(|c| c.into()).collect();
+        let rest = rest.trim();
+        if let Some(key) = rest.strip_prefix("key") {
+            let key = (key
+                .trim()
+                .strip_prefix('(')
+                .and_then(|k| k.strip_suffix(')')))
+            .ok_or_else(invalid)?;
+            let key = key.split_whitespace().map(|c| c.into()).collect();
+            self.analysis.primary_keys.insert(name.into(), key);
+        } else if !rest.is_empty() {
+            return Err(invalid());
+        }
+        let mut table = Table {
+            columns,
+            rows: vec![],
+        };
+        for row in rows.iter().filter(|l| !l.starts_with('#')) {
+            let row = parse_row(row)?;
+            if row.len() != table.columns.len() {
+                return Err(format!(
+                    "expect {} values in table {name}, found {}",
+                    table.columns.len(),
+                    row.len()
+                ));
+            }
+            table.rows.push(row);
+        }
+        self.tables.insert(name.into(), table);
+        Ok(())
+    }
+
+    /// Runs the query and writes it to the output with the actual sections.
+    fn query(&mut self, header: &str, lines: &[&str]) -> Result<(), String> {
+        let mut rowsort = false;
+        for option in header.split_whitespace().skip(1) {
+            match option {
+                "rowsort" => rowsort = true,
+                _ => return Err(format!("unknown query option: {option}")),
+            }
+        }
+        let input_end = (lines.iter())
+            .position(|l| l.starts_with("----"))
+            .unwrap_or(lines.len());
+        let input = &lines[..input_end];
+
+        // the expected sections
+        let mut expected: Vec<(&str, Vec<&str>)> = vec![];
+        for line in lines[input_end..].iter() {
+            match line.strip_prefix("----") {
+                Some(name) => expected.push((name.trim(), vec![])),
+                None => expected.last_mut().unwrap().1.push(line),
+            }
+        }
+        let section =
+            |name: &str| (expected.iter()).find_map(|(n, lines)| (*n == name).then_some(lines));
+
+        let mut actual = self.execute(&input.join("\n"));
+        if let Ok(actual) = &mut actual {
+            if rowsort {
+                actual.rows.sort();
+            }
+        }
+
+        // write the query with the actual sections
+        self.output.push(header.into());
+        self.output.extend(input.iter().map(|l| l.to_string()));
+        match &actual {
+            Ok(actual) => {
+                let all = expected.is_empty() || section("error").is_some();
+                if all || section("plan").is_some() {
+                    self.output.push("---- plan".into());
+                    self.output.push(actual.plan.clone());
+                }
+                if all || section("result").is_some() {
+                    self.output.push("---- result".into());
+                    self.output.extend(actual.rows.iter().cloned());
+                }
+            }
+            Err(error) => {
+                self.output.push("---- error".into());
+                self.output.push(error.clone());
+            }
+        }
+
+        if let Ok(actual) = &actual {
+            let (mut rows, mut input_rows) = (actual.rows.clone(), actual.input_rows.clone());
+            rows.sort();
+            input_rows.sort();
+            if rows != input_rows {
+                return Err(format!(
+                    "the optimized plan returns different rows\n[input]\n{}\n[optimized]\n{}",
+                    input_rows.join("\n"),
+                    rows.join("\n")
+                ));
+            }
+        }
+
+        // compare the actual output with the expected sections
+        for (name, lines) in expected.iter() {
+            match (*name, &actual) {
+                ("plan", Ok(actual)) => {
+                    let expected = lines.join("\n");
+                    let same = match expected.parse::<RecExpr>() {
+                        Ok(plan) => {
+                            actual.plan.parse::<RecExpr>().unwrap().to_string() == plan.to_string()
+                        }
+                        Err(_) => false,
+                    };
+                    if !same {
+                        return Err(format!(
+                            "plan mismatch\n[expected]\n{expected}\n[actual]\n{}",
+                            actual.plan
+                        ));
+                    }
+                }
+                ("result", Ok(actual)) => {
+                    let expected: Vec<_> = lines.iter().map(|l| l.trim()).collect();
+                    if expected != actual.rows {
+                        return Err(format!(
+                            "result mismatch\n[expected]\n{}\n[actual]\n{}",
+                            expected.join("\n"),
+                            actual.rows.join("\n")
+                        ));
+                    }
+                }
+                ("error", Ok(_)) => {
+                    return Err(format!("expect error: {}", lines.join("\n")));
+                }
+                ("plan" | "result", Err(error)) => {
+                    return Err(format!("unexpected error: {error}"))
+                }
+                ("error", Err(error)) => {
+                    if lines.join("\n").trim() != error {
+                        return Err(format!(
+                            "error mismatch\n[expected]\n{}\n[actual]\n{error}",
+                            lines.join("\n")
+                        ));
+                    }
+                }
+                _ => return Err(format!("unknown section: {name}")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Optimizes and executes the plan.
+    ///
+    /// Returns an error if the plan is invalid, or any plan fails to execute.
+    fn execute(&self, input: &str) -> Result<Actual, String> {
+        if !input.trim_start().starts_with('(') {
+            return Err("SQL is not supported, write the plan as an S-expression".into());
+        }
+        let expr: RecExpr = input.parse().map_err(|e| format!("{e}"))?;
+        let (best, _) = (Optimizer::new().analysis(self.analysis.clone()))
+            .optimize(&expr)
+            .map_err(|e| e.to_string())?;
+        let expected = eval::execute(&self.tables, &expr).map_err(|e| e.to_string())?;
+        let rows = eval::execute(&self.tables, &best)
+            .map_err(|e| format!("failed to execute the optimized plan: {e}"))?;
+        Ok(Actual {
+            plan: best.pretty(80),
+            rows: format_rows(&rows),
+            input_rows: format_rows(&expected),
+        })
+    }
+}
+
+/// Formats each row as its values separated by spaces.
+fn format_rows(rows: &[Row]) -> Vec<String> {
+    (rows.iter())
+        .map(|row| {
+            let values: Vec<_> = row.iter().map(|v| v.to_string()).collect();
+            values.join(" ")
+        })
+        .collect()
+}
+
+/// Parses the values separated by spaces. Strings are quoted and may contain spaces.
+fn parse_row(line: &str) -> Result<Row, String> {
+    let line = line.trim();
+    let mut values = vec![];
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut end = line.len();
+        let mut quoted = c == '\'';
+        while let Some((i, c)) = chars.peek().cloned() {
+            if c == '\'' {
+                quoted = !quoted;
+            } else if c.is_whitespace() && !quoted {
+                end = i;
+                break;
+            }
+            chars.next();
+        }
+        let value = &line[start..end];
+        values.push(
+            value
+                .parse()
+                .map_err(|_| format!("invalid value: {value}"))?,
+        );
+    }
+    Ok(values)
+}
@@ -0,0 +1,116 @@
+//! Trace the rules that derive the optimized plan from the input, using egg's explanations.
+
+use std::fmt::{self, Display, Formatter};
+
+use egg::{Condition, Explanation, FlatTerm, Subst, Symbol};
+
+use super::*;
+
+/// The conditions that passed for each rule, in the order they are checked.
+pub type Conditions = HashMap<Symbol, Vec<&'static str>>;
+
+/// A condition of a rule with its source text. It is created by `rw!`.
+///
+/// If explanations are enabled, the text is recorded in [`ExprAnalysis::conditions`]
+/// when the condition passes, so the trace shows the checks that ran in the egraph.
+pub(crate) struct Check<C> {
+    rule: Symbol,
+    text: &'static str,
+    condition: C,
+}
+
+impl<C> Check<C> {
+    pub(crate) fn new(rule: impl Into<Symbol>, text: &'static str, condition: C) -> Self {
+        Check {
+            rule: rule.into(),
+            text,
+            condition,
+        }
+    }
+}
+
+impl<C: Condition<Expr, ExprAnalysis>> Condition<Expr, ExprAnalysis> for Check<C> {
+    fn check(&self, egraph: &mut EGraph, eclass: Id, subst: &Subst) -> bool {
+        let passed = self.condition.check(egraph, eclass, subst);
+        if passed && egraph.are_explanations_enabled() {
+            let texts = egraph.analysis.conditions.entry(self.rule).or_default();
+            if !texts.contains(&self.text) {
+                texts.push(self.text);
+            }
+        }
+        passed
+    }
+
+    fn vars(&self) -> Vec<egg::Var> {
+        self.condition.vars()
+    }
+}
+
+/// A step of the trace: a rule is applied to the plan of the previous step.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// The name of the optimizer stage.
+    pub stage: &'static str,
+    /// The name of the rule.
+    pub rule: String,
+    /// True if the rule is applied from its right-hand side to the left-hand side.
+    pub reverse: bool,
+    /// The conditions of the rule, which passed when it was applied.
+    pub conditions: Vec<&'static str>,
+    /// The plan after the rule is applied.
+    pub plan: RecExpr,
+}
+
+impl Display for TraceStep {
+    /// Displays the step as:
+    ///
+    /// ```text
+    /// [stage] rule
+    ///   if condition
+    ///   => plan
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.stage, self.rule)?;
+        if self.reverse {
+            write!(f, " (reverse)")?;
+        }
+        writeln!(f)?;
+        for condition in &self.conditions {
+            writeln!(f, "  if {condition}")?;
+        }
+        writeln!(f, "  => {}", self.plan)
+    }
+}
+
+/// Returns the steps of the explanation in the stage,
+/// with the conditions that passed in the egraph of the stage.
+pub(crate) fn steps(
+    stage: &'static str,
+    conditions: &Conditions,
+    explanation: &mut Explanation<Expr>,
+) -> Vec<TraceStep> {
+    let terms = explanation.make_flat_explanation();
+    (terms.iter().skip(1))
+        .map(|term| {
+            let (rule, reverse) = find_rule(term).expect("each step applies a rule");
+            TraceStep {
+                stage,
+                conditions: conditions.get(&rule).cloned().unwrap_or_default(),
+                rule: rule.to_string(),
+                reverse,
+                plan: term.get_recexpr(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the rule applied in the term and whether it is applied in reverse.
+fn find_rule(term: &FlatTerm<Expr>) -> Option<(egg::Symbol, bool)> {
+    if let Some(rule) = term.forward_rule {
+        return Some((rule, false));
+    }
+    if let Some(rule) = term.backward_rule {
+        return Some((rule, true));
+    }
+    term.children.iter().find_map(find_rule)
+}
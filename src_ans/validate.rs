@@ -0,0 +1,387 @@
+//! Validate the structure of a plan before optimizing it.
+//!
+//! The rules and analyses assume that the input is well-formed, e.g. the first child of
+//! `proj` is a list, and may panic otherwise.
+
+use std::collections::HashSet;
+
+use egg::Language;
+
+use super::*;
+
+/// The path from the root to a node: the index of the child at each level.
+pub type Path = Vec<usize>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlanError {
+    // #[error("the plan is empty")]
+    Empty,
+    // #[error("expected a plan at {path:?}, found {found}")]
+    ExpectPlan {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected an expression at {path:?}, found {found}")]
+    ExpectExpr {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a list at {path:?}, found {found}")]
+    ExpectList {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a column at {path:?}, found {found}")]
+    ExpectColumn {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected a join type at {path:?}, found {found}")]
+    ExpectJoinType {
+        path: Path,
+        found: String,
+    },
+    // #[error("expected {expected} elements at {path:?}, found {found}")]
+    LengthMismatch {
+        path: Path,
+        expected: usize,
+        found: usize,
+    },
+    // #[error("column {column} not found at {path:?}")]
+    ColumnNotFound {
+        path: Path,
+        column: String,
+    },
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use PlanError::*;
+        match self {
+            Empty => write!(f, "the plan is empty"),
+            ExpectPlan { path, found } => write!(f, "expected a plan at {path:?}, found {found}"),
+            ExpectExpr { path, found } => {
+                write!(f, "expected an expression at {path:?}, found {found}")
+            }
+            ExpectList { path, found } => write!(f, "expected a list at {path:?}, found {found}"),
+            ExpectColumn { path, found } => {
+                write!(f, "expected a column at {path:?}, found {found}")
+            }
+            ExpectJoinType { path, found } => {
+                write!(f, "expected a join type at {path:?}, found {found}")
+            }
+            LengthMismatch {
+                path,
+                expected,
+                found,
+            } => write!(f, "expected {expected} elements at {path:?}, found {found}"),
+            ColumnNotFound { path, column } => write!(f, "column {column} not found at {path:?}"),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Checks that the plan is well-formed:
+///
+/// - children in plan positions are plans, and the others are expressions
+/// - operands that must be lists are lists, with the same length if they are paired
+/// - columns used by a plan node are produced by its children
+///
+/// If the root is not a plan, it is validated as an expression without checking columns.
+pub fn validate(expr: &RecExpr) -> Result<(), PlanError> {
+    if expr.as_ref().is_empty() {
+        return Err(PlanError::Empty);
+    }
+    let root = Id::from(expr.as_ref().len() - 1);
+    let validator = Validator { expr };
+    if is_plan(&expr[root]) {
+        validator.plan(root, &[])?;
+    } else {
+        validator.expr(root, None, &[])?;
+    }
+    Ok(())
+}
+
+/// Returns true if the node is a plan.
+pub(crate) fn is_plan(enode: &Expr) -> bool {
+    use Expr::*;
+    matches!(
+        enode,
+        Scan(_)
+            | Values(_)
+            | Proj(_)
+            | Filter(_)
+            | Order(_)
+            | Limit(_)
+            | TopN(_)
+            | Join(_)
+            | HashJoin(_)
+            | MergeJoin(_)
+            | NestedLoopJoin(_)
+            | Agg(_)
+            | FinalAgg(_)
+            | Window(_)
+            | Distinct(_)
+            | Union(_)
+            | UnionAll(_)
+            | Intersect(_)
+            | Except(_)
+            | Empty(_)
+    )
+}
+
+/// The expressions produced by a plan, rendered as strings.
+type Scope = HashSet<String>;
+
+struct Validator<'a> {
+    expr: &'a RecExpr,
+}
+
+impl Validator<'_> {
+    /// Validates the plan `id` and returns its output expressions.
+    fn plan(&self, id: Id, path: &[usize]) -> Result<Vec<String>, PlanError> {
+        use Expr::*;
+        let child = |i: usize| [path, &[i]].concat();
+        let scope = |outputs: &[String]| -> Scope { outputs.iter().cloned().collect() };
+        Ok(match &self.expr[id] {
+            Scan([table, columns]) => {
+                self.column(*table, &child(0))?;
+                let columns = self.list(*columns, &child(1))?;
+                for (i, column) in columns.iter().enumerate() {
+                    self.column(*column, &[path, &[1, i]].concat())?;
+                }
+                columns.iter().map(|id| self.render(*id)).collect()
+            }
+            Values(rows) => {
+                let mut outputs: Option<Vec<String>> = None;
+                for (i, row) in rows.iter().enumerate() {
+                    let row = self.exprs(*row, Some(&Scope::new()), &child(i))?;
+                    match &outputs {
+                        Some(first) if first.len() != row.len() => {
+                            return Err(PlanError::LengthMismatch {
+                                path: child(i),
+                                expected: first.len(),
+                                found: row.len(),
+                            });
+                        }
+                        Some(_) => {}
+                        None => outputs = Some(row),
+                    }
+                }
+                outputs.unwrap_or_default()
+            }
+            Proj([exprs, c]) => {
+                let outputs = self.plan(*c, &child(1))?;
+                self.exprs(*exprs, Some(&scope(&outputs)), &child(0))?
+            }
+            Filter([cond, c]) => {
+                let outputs = self.plan(*c, &child(1))?;
+                self.expr(*cond, Some(&scope(&outputs)), &child(0))?;
+                outputs
+            }
+            Order([keys, c]) => {
+                let outputs = self.plan(*c, &child(1))?;
+                self.exprs(*keys, Some(&scope(&outputs)), &child(0))?;
+                outputs
+            }
+            Limit([limit, offset, c]) => {
+                let outputs = self.plan(*c, &child(2))?;
+                self.expr(*limit, Some(&Scope::new()), &child(0))?;
+                self.expr(*offset, Some(&Scope::new()), &child(1))?;
+                outputs
+            }
+            TopN([limit, offset, keys, c]) => {
+                let outputs = self.plan(*c, &child(3))?;
+                self.expr(*limit, Some(&Scope::new()), &child(0))?;
+                self.expr(*offset, Some(&Scope::new()), &child(1))?;
+                self.exprs(*keys, Some(&scope(&outputs)), &child(2))?;
+                outputs
+            }
+            Join([ty, cond, l, r]) | NestedLoopJoin([ty, cond, l, r]) => {
+                self.join_type(*ty, &child(0))?;
+                let mut outputs = self.plan(*l, &child(2))?;
+                outputs.extend(self.plan(*r, &child(3))?);
+                self.expr(*cond, Some(&scope(&outputs)), &child(1))?;
+                outputs
+            }
+            HashJoin([ty, lkeys, rkeys, l, r]) | MergeJoin([ty, lkeys, rkeys, l, r]) => {
+                self.join_type(*ty, &child(0))?;
+                let left = self.plan(*l, &child(3))?;
+                let right = self.plan(*r, &child(4))?;
+                let lkeys = self.exprs(*lkeys, Some(&scope(&left)), &child(1))?;
+                let rkeys = self.exprs(*rkeys, Some(&scope(&right)), &child(2))?;
+                if lkeys.len() != rkeys.len() {
+                    return Err(PlanError::LengthMismatch {
+                        path: child(2),
+                        expected: lkeys.len(),
+                        found: rkeys.len(),
+                    });
+                }
+                [left, right].concat()
+            }
+            Agg([aggs, group_keys, c]) => {
+                let outputs = scope(&self.plan(*c, &child(2))?);
+                let mut aggs = self.exprs(*aggs, Some(&outputs), &child(0))?;
+                aggs.extend(self.group_keys(*group_keys, &outputs, &child(1))?);
+                aggs
+            }
+            // the aggregations are computed from their states in the child
+            FinalAgg([aggs, group_keys, c]) => {
+                let outputs = scope(&self.plan(*c, &child(2))?);
+                let mut aggs = self.exprs(*aggs, None, &child(0))?;
+                aggs.extend(self.group_keys(*group_keys, &outputs, &child(1))?);
+                aggs
+            }
+            Window([exprs, c]) => {
+                let mut outputs = self.plan(*c, &child(1))?;
+                let windows = self.exprs(*exprs, Some(&scope(&outputs)), &child(0))?;
+                outputs.extend(windows);
+                outputs
+            }
+            Distinct(c) => self.plan(*c, &child(0))?,
+            Union([l, r]) | UnionAll([l, r]) | Intersect([l, r]) | Except([l, r]) => {
+                let left = self.plan(*l, &child(0))?;
+                let right = self.plan(*r, &child(1))?;
+                if left.len() != right.len() {
+                    return Err(PlanError::LengthMismatch {
+                        path: child(1),
+                        expected: left.len(),
+                        found: right.len(),
+                    });
+                }
+                left
+            }
+            // the child is either a plan or a list of expressions
+            Empty(c) => match &self.expr[*c] {
+                List(_) => self.exprs(*c, None, &child(0))?,
+                _ => self.plan(*c, &child(0))?,
+            },
+            _ => {
+                return Err(PlanError::ExpectPlan {
+                    path: path.to_vec(),
+                    found: self.render(id),
+                })
+            }
+        })
+    }
+
+    /// Validates the expression `id`.
+    ///
+    /// Columns must be in `scope` if it is some.
+    fn expr(&self, id: Id, scope: Option<&Scope>, path: &[usize]) -> Result<(), PlanError> {
+        use Expr::*;
+        let enode = &self.expr[id];
+        if scope.is_some_and(|scope| scope.contains(&self.render(id))) {
+            // an output of the child
+            return Ok(());
+        }
+        match enode {
+            _ if is_plan(enode) => Err(PlanError::ExpectExpr {
+                path: path.to_vec(),
+                found: self.render(id),
+            }),
+            List(_) | Inner | LeftOuter | RightOuter | FullOuter | ColumnMerge(_)
+            | ColumnPrune(_) | GroupingSets(_) | Rollup(_) | Cube(_) | Alias(_) => {
+                Err(PlanError::ExpectExpr {
+                    path: path.to_vec(),
+                    found: self.render(id),
+                })
+            }
+            Column(_) | Nested(_) if scope.is_some() => {
+                let column = match enode {
+                    Nested(c) => self.render(*c),
+                    _ => enode.to_string(),
+                };
+                if scope.is_some_and(|scope| scope.contains(&column)) {
+                    return Ok(());
+                }
+                Err(PlanError::ColumnNotFound {
+                    path: path.to_vec(),
+                    column,
+                })
+            }
+            Over([f, partition, order]) => {
+                self.expr(*f, scope, &[path, &[0]].concat())?;
+                self.exprs(*partition, scope, &[path, &[1]].concat())?;
+                self.exprs(*order, scope, &[path, &[2]].concat())?;
+                Ok(())
+            }
+            _ => {
+                for (i, child) in enode.children().iter().enumerate() {
+                    self.expr(*child, scope, &[path, &[i]].concat())?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Validates the list of expressions and returns them.
+    fn exprs(
+        &self,
+        id: Id,
+        scope: Option<&Scope>,
+        path: &[usize],
+    ) -> Result<Vec<String>, PlanError> {
+        let list = self.list(id, path)?;
+        for (i, expr) in list.iter().enumerate() {
+            self.expr(*expr, scope, &[path, &[i]].concat())?;
+        }
+        Ok(list.iter().map(|id| self.render(*id)).collect())
+    }
+
+    /// Validates the group keys, which is a list or grouping sets, and returns all keys.
+    fn group_keys(&self, id: Id, scope: &Scope, path: &[usize]) -> Result<Vec<String>, PlanError> {
+        let Expr::GroupingSets(sets) = &self.expr[id] else {
+            return self.exprs(id, Some(scope), path);
+        };
+        let mut keys = vec![];
+        let path = [path, &[0]].concat();
+        for (i, set) in self.list(*sets, &path)?.iter().enumerate() {
+            for key in self.exprs(*set, Some(scope), &[&path[..], &[i]].concat())? {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    fn list(&self, id: Id, path: &[usize]) -> Result<&[Id], PlanError> {
+        match &self.expr[id] {
+            Expr::List(list) => Ok(list),
+            _ => Err(PlanError::ExpectList {
+                path: path.to_vec(),
+                found: self.render(id),
+            }),
+        }
+    }
+
+    fn column(&self, id: Id, path: &[usize]) -> Result<(), PlanError> {
+        match &self.expr[id] {
+            Expr::Column(_) => Ok(()),
+            _ => Err(PlanError::ExpectColumn {
+                path: path.to_vec(),
+                found: self.render(id),
+            }),
+        }
+    }
+
+    fn join_type(&self, id: Id, path: &[usize]) -> Result<(), PlanError> {
+        use Expr::*;
+        match &self.expr[id] {
+            Inner | LeftOuter | RightOuter | FullOuter => Ok(()),
+            _ => Err(PlanError::ExpectJoinType {
+                path: path.to_vec(),
+                found: self.render(id),
+            }),
+        }
+    }
+
+    /// Returns the string of the expression `id`.
+    fn render(&self, id: Id) -> String {
+        self.expr[id]
+            .build_recexpr(|i| self.expr[i].clone())
+            .to_string()
+    }
+}
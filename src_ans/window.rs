@@ -0,0 +1,58 @@
+//! Analyze window functions.
+
+use egg::{Language, Subst};
+
+use super::*;
+
+/// The data type of window function analysis.
+pub type WindowSet = Vec<Expr>;
+
+/// Returns all window functions in the tree.
+///
+/// Note: if there is a window function over window function, only the upper one will be returned.
+pub fn analyze_windows(egraph: &EGraph, enode: &Expr) -> WindowSet {
+    use Expr::*;
+    let x = |i: &Id| egraph[*i].data.windows.clone();
+    match enode {
+        Over(_) => vec![enode.clone()],
+        // merge the set from all children
+        Nested(_) | List(_) | Neg(_) | Not(_) | IsNull(_) | Add(_) | Sub(_) | Mul(_) | Div(_)
+        | Eq(_) | NotEq(_) | Gt(_) | Lt(_) | GtEq(_) | LtEq(_) | And(_) | Or(_) | Xor(_)
+        | Asc(_) | Desc(_) | Max(_) | Min(_) | Sum(_) | Avg(_) | Count(_) | CountDistinct(_)
+        | SumDistinct(_) => enode.children().iter().flat_map(x).collect(),
+        // ignore plan nodes
+        _ => vec![],
+    }
+}
+
+/// Returns true if all window functions in the lists `var1` and `var2`
+/// have the same partition keys and order keys.
+pub fn is_same_window_spec(var1: &str, var2: &str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
+    let var1 = var(var1);
+    let var2 = var(var2);
+    move |egraph, _, subst| {
+        let specs: Vec<_> = (list(egraph, subst[var1]).into_iter())
+            .chain(list(egraph, subst[var2]))
+            .map(|id| spec(egraph, id))
+            .collect();
+        specs.first().is_some_and(Option::is_some) && specs.iter().all(|s| *s == specs[0])
+    }
+}
+
+/// Returns the partition keys and order keys of the window function in the eclass.
+fn spec(egraph: &EGraph, id: Id) -> Option<[Id; 2]> {
+    egraph[id].iter().find_map(|e| match e {
+        Expr::Over([_, partition, order]) => Some([egraph.find(*partition), egraph.find(*order)]),
+        _ => None,
+    })
+}
+
+/// Returns the elements of the list node in the eclass.
+fn list(egraph: &EGraph, id: Id) -> Vec<Id> {
+    (egraph[id].iter())
+        .find_map(|e| match e {
+            Expr::List(list) => Some(list.to_vec()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+
+use egg::{Id, Searcher};
+use sql_optimizer_labs::{
+    cost::CostFn, expr, join_order::disconnected_relations, plan, EGraph, ExprAnalysis, RecExpr,
+    Rewrite,
+};
+
+fn rules() -> Vec<Rewrite> {
+    let mut rules = vec![];
+    rules.extend(expr::rules());
+    rules.extend(plan::join_rules());
+    rules.extend(plan::join_reorder_rules());
+    rules
+}
+
+#[test]
+fn join_rotate_left() {
+    // SELECT * FROM t1
+    // INNER JOIN (t2 INNER JOIN t3 ON t2.k = t3.k) ON t1.id = t2.id
+    // WHERE t1.a = 1
+    test(
+        "
+        (join inner (= t1.id t2.id)
+            (filter (= t1.a 1) (scan t1 (list t1.id t1.a)))
+            (join inner (= t2.k t3.k)
+                (scan t2 (list t2.id t2.k))
+                (scan t3 (list t3.k t3.c))
+        ))",
+        "
+        (hashjoin inner (list t2.k) (list t3.k)
+            (hashjoin inner (list t1.id) (list t2.id)
+                (filter (= t1.a 1) (scan t1 (list t1.id t1.a)))
+                (scan t2 (list t2.id t2.k))
+            )
+            (scan t3 (list t3.k t3.c))
+        )",
+    );
+}
+
+#[test]
+fn avoid_cross_join() {
+    // SELECT t1.a, t2.b, t3.c FROM t1, t3, t2
+    // WHERE t1.id = t2.id AND t2.k = t3.k AND t3.c = 1
+    test(
+        "
+        (proj (list t1.a t2.b t3.c)
+        (join inner (and (= t1.id t2.id) (= t2.k t3.k))
+            (join inner true
+                (scan t1 (list t1.id t1.a))
+                (filter (= t3.c 1) (scan t3 (list t3.k t3.c)))
+            )
+            (scan t2 (list t2.id t2.k t2.b))
+        ))",
+        "
+        (proj (list t1.a t2.b t3.c)
+        (hashjoin inner (list t2.id) (list t1.id)
+            (hashjoin inner (list t3.k) (list t2.k)
+                (filter (= t3.c 1) (scan t3 (list t3.k t3.c)))
+                (scan t2 (list t2.id t2.k t2.b))
+            )
+            (scan t1 (list t1.id t1.a))
+        ))",
+    );
+}
+
+#[test]
+fn no_new_cross_join() {
+    // SELECT t1.a FROM t1
+    // INNER JOIN t2 ON t1.id = t2.id
+    // INNER JOIN t3 ON t2.k = t3.k
+    let plan = "
+        (proj (list t1.a)
+        (join inner (= t2.k t3.k)
+            (join inner (= t1.id t2.id)
+                (scan t1 (list t1.id t1.a))
+                (scan t2 (list t2.id t2.k))
+            )
+            (scan t3 (list t3.k t3.c))
+        ))";
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&rules());
+    let cross_join: egg::Pattern<_> = "(join inner true ?left ?right)".parse().unwrap();
+    assert!(cross_join.search(&runner.egraph).is_empty());
+}
+
+#[test]
+fn no_new_cross_join_from_binder() {
+    // the main block of TPC-H Q2, whose FROM clause is a chain of cross joins
+    let plan: RecExpr = "
+        (proj (list s.name n.name)
+        (filter (and (= r.id n.region) (and (= n.id s.nation) (and (= p.size 15)
+                (and (= ps.supp s.id) (and (= r.name 'EUROPE') (= ps.part p.id))))))
+            (join inner true
+                (join inner true
+                    (join inner true
+                        (join inner true
+                            (scan part (list p.id p.size))
+                            (scan supplier (list s.id s.name s.nation)))
+                        (scan partsupp (list ps.part ps.supp)))
+                    (scan nation (list n.id n.name n.region)))
+                (scan region (list r.id r.name)))))"
+        .parse()
+        .unwrap();
+    // the conditions connect all tables, so no rotation may add a cross join
+    let analysis = ExprAnalysis {
+        connected_joins: disconnected_relations(&plan).is_empty(),
+        ..Default::default()
+    };
+    assert!(analysis.connected_joins);
+    let mut rules = rules();
+    rules.extend(plan::predicate_pushdown_rules());
+    let runner = egg::Runner::<_, _, ()>::new(analysis)
+        .with_expr(&plan)
+        .run(&rules);
+
+    let mut egraph = EGraph::default();
+    egraph.add_expr(&plan);
+    egraph.rebuild();
+    assert_eq!(cross_joins(&runner.egraph), cross_joins(&egraph));
+}
+
+/// Returns the columns of both sides of all cross joins in the egraph.
+fn cross_joins(egraph: &EGraph) -> BTreeSet<[Vec<String>; 2]> {
+    let columns = |id: Id| {
+        let mut columns: Vec<_> = (egraph[id].data.columns.iter())
+            .map(|c| c.to_string())
+            .collect();
+        columns.sort();
+        columns
+    };
+    let pattern: egg::Pattern<_> = "(join inner true ?left ?right)".parse().unwrap();
+    (pattern.search(egraph).into_iter())
+        .flat_map(|m| m.substs)
+        .map(|subst| {
+            let mut sides = ["?left", "?right"].map(|v| columns(subst[v.parse().unwrap()]));
+            sides.sort();
+            sides
+        })
+        .collect()
+}
+
+#[test]
+fn eight_tables_saturate() {
+    // SELECT t1.id FROM t1
+    // INNER JOIN t2 ON t1.k = t2.id
+    // ...
+    // INNER JOIN t8 ON t7.k = t8.id
+    let mut plan = "(scan t1 (list t1.id t1.k))".to_string();
+    for i in 2..=8 {
+        plan = format!(
+            "(join inner (= t{}.k t{i}.id) {plan} (scan t{i} (list t{i}.id t{i}.k)))",
+            i - 1
+        );
+    }
+    let plan = format!("(proj (list t1.id) {plan})");
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&rules());
+    assert!(
+        matches!(runner.stop_reason, Some(egg::StopReason::Saturated)),
+        "{:?}",
+        runner.stop_reason
+    );
+}
+
+#[track_caller]
+fn test(input: &str, expected: &str) {
+    let runner = egg::Runner::default()
+        .with_expr(&input.parse().unwrap())
+        .run(&rules());
+    let extractor = egg::Extractor::new(
+        &runner.egraph,
+        CostFn {
+            egraph: &runner.egraph,
+        },
+    );
+    let (_, actual) = extractor.find_best(runner.roots[0]);
+    let expected = expected.parse::<RecExpr>().unwrap();
+    assert_eq!(actual.to_string(), expected.to_string());
+}
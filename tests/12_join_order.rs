@@ -0,0 +1,216 @@
+use sql_optimizer_labs::{
+    join_order::{cross_products, disconnected_relations, reorder_joins},
+    RecExpr,
+};
+
+#[test]
+fn restore_column_order() {
+    // SELECT * FROM t1, t3, t2
+    // WHERE t1.id = t2.id AND t2.k = t3.k
+    test(
+        "
+        (filter (and (= t1.id t2.id) (= t2.k t3.k))
+        (join inner true
+            (join inner true
+                (scan t1 (list t1.id t1.a))
+                (scan t3 (list t3.k t3.c))
+            )
+            (scan t2 (list t2.id t2.k))
+        ))",
+        "
+        (proj (list t1.id t1.a t3.k t3.c t2.id t2.k)
+        (hashjoin inner (list t2.k) (list t3.k)
+            (hashjoin inner (list t1.id) (list t2.id)
+                (scan t1 (list t1.id t1.a))
+                (scan t2 (list t2.id t2.k))
+            )
+            (scan t3 (list t3.k t3.c))
+        ))",
+    );
+}
+
+#[test]
+fn bushy_join() {
+    // SELECT t1.a FROM t1
+    // INNER JOIN t2 ON t1.k = t2.id
+    // INNER JOIN t3 ON t2.k = t3.id
+    // INNER JOIN t4 ON t3.k = t4.k
+    // WHERE t4.b = 1
+    test(
+        "
+        (proj (list t1.a)
+        (join inner (= t3.k t4.k)
+            (join inner (= t2.k t3.id)
+                (join inner (= t1.k t2.id)
+                    (scan t1 (list t1.k t1.a))
+                    (scan t2 (list t2.id t2.k))
+                )
+                (scan t3 (list t3.id t3.k))
+            )
+            (filter (= t4.b 1) (scan t4 (list t4.k t4.b)))
+        ))",
+        "
+        (proj (list t1.a)
+        (hashjoin inner (list t2.k) (list t3.id)
+            (hashjoin inner (list t1.k) (list t2.id)
+                (scan t1 (list t1.k t1.a))
+                (scan t2 (list t2.id t2.k))
+            )
+            (hashjoin inner (list t3.k) (list t4.k)
+                (scan t3 (list t3.id t3.k))
+                (filter (= t4.b 1) (scan t4 (list t4.k t4.b)))
+            )
+        ))",
+    );
+}
+
+#[test]
+fn cross_join() {
+    // SELECT * FROM t1, t2, t3
+    // WHERE t1.a = 1 AND t2.b = t3.b
+    test(
+        "
+        (filter (and (= t1.a 1) (= t2.b t3.b))
+        (join inner true
+            (join inner true
+                (scan t1 (list t1.a))
+                (scan t2 (list t2.b))
+            )
+            (scan t3 (list t3.b))
+        ))",
+        "
+        (proj (list t1.a t2.b t3.b)
+        (join inner true
+            (hashjoin inner (list t2.b) (list t3.b)
+                (scan t2 (list t2.b))
+                (scan t3 (list t3.b))
+            )
+            (filter (= t1.a 1) (scan t1 (list t1.a)))
+        ))",
+    );
+}
+
+#[test]
+fn greedy() {
+    // SELECT t1.id FROM t1
+    // INNER JOIN t2 ON t1.k = t2.id
+    // ...
+    // INNER JOIN t16 ON t15.k = t16.id
+    let mut plan = "(scan t1 (list t1.id t1.k))".to_string();
+    for i in 2..=16 {
+        plan = format!(
+            "(join inner (= t{}.k t{i}.id) {plan} (scan t{i} (list t{i}.id t{i}.k)))",
+            i - 1
+        );
+    }
+    let plan = format!("(proj (list t1.id) {plan})");
+    let actual = reorder_joins(&plan.parse().unwrap()).to_string();
+    assert_eq!(actual.matches("hashjoin").count(), 15, "{actual}");
+    assert!(!actual.contains("(join "), "{actual}");
+}
+
+#[test]
+fn max_relations() {
+    // a region of 64 relations is the largest to be reordered
+    let chain = |n: usize| {
+        let mut plan = "(scan t1 (list t1.id t1.k))".to_string();
+        for i in 2..=n {
+            plan = format!(
+                "(join inner (= t{}.k t{i}.id) {plan} (scan t{i} (list t{i}.id t{i}.k)))",
+                i - 1
+            );
+        }
+        format!("(proj (list t1.id) {plan})")
+    };
+    let actual = reorder_joins(&chain(64).parse().unwrap()).to_string();
+    assert_eq!(actual.matches("hashjoin").count(), 63, "{actual}");
+    assert!(!actual.contains("(join "), "{actual}");
+
+    // a larger one is left unchanged, but the region of its first 64 relations is reordered
+    let actual = reorder_joins(&chain(65).parse().unwrap()).to_string();
+    assert!(actual.starts_with("(proj (list t1.id) (join inner (= t64.k t65.id) (hashjoin "));
+    assert_eq!(actual.matches("hashjoin").count(), 63, "{actual}");
+}
+
+#[test]
+fn predicate_over_three_tables() {
+    // SELECT * FROM a, b, c WHERE a.x + b.y = c.z
+    test(
+        "
+        (filter (= (+ a.x b.y) c.z)
+        (join inner true
+            (join inner true
+                (scan a (list a.x))
+                (scan b (list b.y))
+            )
+            (scan c (list c.z))
+        ))",
+        "
+        (hashjoin inner (list (+ a.x b.y)) (list c.z)
+            (join inner true
+                (scan a (list a.x))
+                (scan b (list b.y))
+            )
+            (scan c (list c.z))
+        )",
+    );
+}
+
+#[test]
+fn find_cross_products() {
+    // SELECT * FROM t1, t2, t3
+    // WHERE t1.a = 1 AND t2.b = t3.b
+    let plan: RecExpr = "
+        (filter (and (= t1.a 1) (= t2.b t3.b))
+        (join inner (= t2.b t3.b)
+            (join inner true
+                (scan t1 (list t1.a))
+                (scan t2 (list t2.b))
+            )
+            (scan t3 (list t3.b))
+        ))"
+    .parse()
+    .unwrap();
+    let joins: Vec<_> = cross_products(&plan)
+        .into_iter()
+        .map(|id| plan[id].to_string())
+        .collect();
+    assert_eq!(joins, ["join"]);
+
+    let disconnected = disconnected_relations(&plan);
+    assert_eq!(disconnected.len(), 1);
+    assert_eq!(
+        disconnected[0].to_string(),
+        "relations are not connected by any condition: [t1], [t2, t3]"
+    );
+}
+
+#[test]
+fn connected_relations() {
+    // SELECT * FROM t1, t2 WHERE t1.a = t2.b
+    let plan: RecExpr = "
+        (filter (= t1.a t2.b)
+        (join inner true
+            (scan t1 (list t1.a))
+            (scan t2 (list t2.b))
+        ))"
+    .parse()
+    .unwrap();
+    assert_eq!(cross_products(&plan).len(), 1);
+    assert!(disconnected_relations(&plan).is_empty());
+}
+
+#[test]
+fn empty_plan() {
+    let plan = RecExpr::default();
+    assert!(reorder_joins(&plan).as_ref().is_empty());
+    assert!(cross_products(&plan).is_empty());
+    assert!(disconnected_relations(&plan).is_empty());
+}
+
+#[track_caller]
+fn test(input: &str, expected: &str) {
+    let actual = reorder_joins(&input.parse().unwrap());
+    let expected = expected.parse::<RecExpr>().unwrap();
+    assert_eq!(actual.to_string(), expected.to_string());
+}
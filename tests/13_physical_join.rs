@@ -0,0 +1,87 @@
+use sql_optimizer_labs::{cost::CostFn, plan::rules, RecExpr};
+
+#[test]
+fn hash_join() {
+    // SELECT * FROM t1 JOIN t2 ON t1.a = t2.a
+    test(
+        "
+        (join inner (= t1.a t2.a)
+            (scan t1 (list t1.a t1.b))
+            (scan t2 (list t2.a t2.c))
+        )",
+        "
+        (hashjoin inner (list t1.a) (list t2.a)
+            (scan t1 (list t1.a t1.b))
+            (scan t2 (list t2.a t2.c))
+        )",
+    );
+}
+
+#[test]
+fn merge_join_on_sorted_inputs() {
+    // SELECT * FROM (SELECT * FROM t1 ORDER BY a) AS t1
+    // JOIN (SELECT * FROM t2 ORDER BY a) AS t2 ON t1.a = t2.a
+    test(
+        "
+        (join inner (= t1.a t2.a)
+            (order (list (asc t1.a)) (scan t1 (list t1.a t1.b)))
+            (order (list (asc t2.a)) (scan t2 (list t2.a t2.c)))
+        )",
+        "
+        (mergejoin inner (list t1.a) (list t2.a)
+            (order (list (asc t1.a)) (scan t1 (list t1.a t1.b)))
+            (order (list (asc t2.a)) (scan t2 (list t2.a t2.c)))
+        )",
+    );
+}
+
+#[test]
+fn order_on_merge_join() {
+    // SELECT * FROM t1 JOIN t2 ON t1.a = t2.a ORDER BY t1.a
+    test(
+        "
+        (order (list (asc t1.a))
+        (join inner (= t1.a t2.a)
+            (scan t1 (list t1.a t1.b))
+            (scan t2 (list t2.a t2.c))
+        ))",
+        "
+        (mergejoin inner (list t1.a) (list t2.a)
+            (order (list (asc t1.a)) (scan t1 (list t1.a t1.b)))
+            (order (list (asc t2.a)) (scan t2 (list t2.a t2.c)))
+        )",
+    );
+}
+
+#[test]
+fn nested_loop_join() {
+    // SELECT * FROM t1 JOIN t2 ON t1.a < t2.a
+    test(
+        "
+        (join inner (< t1.a t2.a)
+            (scan t1 (list t1.a t1.b))
+            (scan t2 (list t2.a t2.c))
+        )",
+        "
+        (nljoin inner (< t1.a t2.a)
+            (scan t1 (list t1.a t1.b))
+            (scan t2 (list t2.a t2.c))
+        )",
+    );
+}
+
+#[track_caller]
+fn test(input: &str, expected: &str) {
+    let runner = egg::Runner::default()
+        .with_expr(&input.parse().unwrap())
+        .run(&rules());
+    let extractor = egg::Extractor::new(
+        &runner.egraph,
+        CostFn {
+            egraph: &runner.egraph,
+        },
+    );
+    let (_, actual) = extractor.find_best(runner.roots[0]);
+    let expected = expected.parse::<RecExpr>().unwrap();
+    assert_eq!(actual.to_string(), expected.to_string());
+}
@@ -0,0 +1,83 @@
+use sql_optimizer_labs::plan::rules;
+
+egg::test_fn! {
+    distinct_on_agg,
+    rules(),
+    "(distinct
+        (agg (list (sum b)) (list a)
+            (scan t (list a b))))" =>
+    "(agg (list (sum b)) (list a)
+        (scan t (list a b)))",
+}
+
+egg::test_fn! {
+    distinct_to_agg,
+    rules(),
+    "(distinct
+        (scan t (list a b)))" =>
+    "(agg (list) (list a b)
+        (scan t (list a b)))",
+}
+
+egg::test_fn! {
+    union_all_on_empty,
+    rules(),
+    "(union_all
+        (scan t1 (list a))
+        (filter false (scan t2 (list b))))" =>
+    "(scan t1 (list a))",
+}
+
+egg::test_fn! {
+    union_on_empty,
+    rules(),
+    "(union
+        (scan t1 (list a))
+        (filter false (scan t2 (list b))))" =>
+    "(distinct (scan t1 (list a)))",
+}
+
+egg::test_fn! {
+    except_on_empty,
+    rules(),
+    "(except
+        (filter false (scan t1 (list a)))
+        (scan t2 (list b)))" =>
+    "(empty (scan t1 (list a)))",
+}
+
+egg::test_fn! {
+    pushdown_filter_union_all,
+    rules(),
+    "(filter (= a 1)
+        (union_all
+            (scan t1 (list a))
+            (scan t2 (list b))))" =>
+    "(union_all
+        (filter (= a 1) (scan t1 (list a)))
+        (filter (= b 1) (scan t2 (list b))))",
+}
+
+egg::test_fn! {
+    pushdown_filter_except,
+    rules(),
+    "(filter (> (+ a c) 1)
+        (except
+            (scan t1 (list a c))
+            (scan t2 (list b d))))" =>
+    "(except
+        (filter (> (+ a c) 1) (scan t1 (list a c)))
+        (filter (> (+ b d) 1) (scan t2 (list b d))))",
+}
+
+egg::test_fn! {
+    pushdown_proj_union_all,
+    rules(),
+    "(proj (list a)
+        (union_all
+            (scan t1 (list a c))
+            (scan t2 (list b d))))" =>
+    "(union_all
+        (proj (list a) (scan t1 (list a)))
+        (proj (list b) (scan t2 (list b))))",
+}
@@ -0,0 +1,194 @@
+use egg::Language;
+use sql_optimizer_labs::{
+    agg::{plan_select, Error},
+    plan::rules,
+    EGraph, RecExpr,
+};
+
+#[test]
+fn window() {
+    // SELECT a, row_number() OVER (PARTITION BY a ORDER BY b) FROM t
+    // ORDER BY rank() OVER (PARTITION BY a ORDER BY b)
+    test(Case {
+        select: "(list a (over row_number (list a) (list (asc b))))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "(list (asc (over rank (list a) (list (asc b)))))",
+        expected: Ok("
+        (proj (list a (` (over row_number (list a) (list (asc b)))))
+            (order (list (asc (` (over rank (list a) (list (asc b))))))
+                (window (list (over row_number (list a) (list (asc b))) (over rank (list a) (list (asc b))))
+                    (filter true
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
+#[test]
+fn window_with_different_specs() {
+    // SELECT sum(b) OVER (PARTITION BY a), sum(b) OVER (ORDER BY b) FROM t
+    test(Case {
+        select: "(list (over (sum b) (list a) list) (over (sum b) list (list (asc b))))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` (over (sum b) (list a) list)) (` (over (sum b) list (list (asc b)))))
+            (order list
+                (window (list (over (sum b) list (list (asc b))))
+                    (window (list (over (sum b) (list a) list))
+                        (filter true
+                            (filter true
+                                (scan t (list a b))
+        ))))))"),
+    });
+}
+
+#[test]
+fn window_over_agg() {
+    // SELECT a, rank() OVER (ORDER BY sum(b)) FROM t GROUP BY a
+    test(Case {
+        select: "(list a (over rank list (list (asc (sum b)))))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "(list a)",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` a) (` (over rank list (list (asc (` (sum b)))))))
+            (order list
+                (window (list (over rank list (list (asc (` (sum b))))))
+                    (filter true
+                        (agg (list (sum b)) (list a)
+                            (filter true
+                                (scan t (list a b))
+        ))))))"),
+    });
+}
+
+#[test]
+fn error_window_in_where() {
+    // SELECT a FROM t WHERE row_number() OVER () > 1
+    test(Case {
+        select: "(list a)",
+        from: "(scan t (list a b))",
+        where_: "(> (over row_number list list) 1)",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Err(Error::WindowInWhere),
+    });
+}
+
+#[test]
+fn error_nested_window() {
+    // SELECT sum(row_number() OVER ()) OVER () FROM t
+    test(Case {
+        select: "(list (over (sum (over row_number list list)) list list))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Err(Error::NestedWindow(
+            "(over (sum (over row_number list list)) list list)".into(),
+        )),
+    });
+}
+
+#[test]
+fn error_window_in_agg() {
+    // SELECT sum(row_number() OVER ()) FROM t
+    test(Case {
+        select: "(list (sum (over row_number list list)))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Err(Error::WindowInAgg(
+            "(sum (over row_number list list))".into(),
+        )),
+    });
+}
+
+egg::test_fn! {
+    window_merge,
+    rules(),
+    "(window (list (over rank (list a) (list (asc b))))
+        (window (list (over row_number (list a) (list (asc b))))
+            (scan t (list a b))))" =>
+    "(window (list (over row_number (list a) (list (asc b))) (over rank (list a) (list (asc b))))
+        (scan t (list a b)))",
+}
+
+egg::test_fn! {
+    pushdown_proj_window,
+    rules(),
+    "(proj (list (` (over row_number (list a) list)))
+        (window (list (over row_number (list a) list))
+            (scan t (list a b c))))" =>
+    "(proj (list (` (over row_number (list a) list)))
+        (window (list (over row_number (list a) list))
+            (scan t (list a))))",
+}
+
+struct Case {
+    select: &'static str,
+    from: &'static str,
+    where_: &'static str,
+    having: &'static str,
+    groupby: &'static str,
+    orderby: &'static str,
+    expected: Result<&'static str, Error>,
+}
+
+#[track_caller]
+fn test(mut case: Case) {
+    if case.where_.is_empty() {
+        case.where_ = "true";
+    }
+    if case.having.is_empty() {
+        case.having = "true";
+    }
+    if case.groupby.is_empty() {
+        case.groupby = "list";
+    }
+    if case.orderby.is_empty() {
+        case.orderby = "list";
+    }
+    let mut egraph = EGraph::default();
+    let projection = egraph.add_expr(&case.select.parse().unwrap());
+    let from = egraph.add_expr(&case.from.parse().unwrap());
+    let where_ = egraph.add_expr(&case.where_.parse().unwrap());
+    let having = egraph.add_expr(&case.having.parse().unwrap());
+    let groupby = egraph.add_expr(&case.groupby.parse().unwrap());
+    let orderby = egraph.add_expr(&case.orderby.parse().unwrap());
+    match plan_select(
+        &mut egraph,
+        from,
+        where_,
+        having,
+        groupby,
+        orderby,
+        projection,
+    ) {
+        Err(e) => assert_eq!(case.expected, Err(e)),
+        Ok(id) => {
+            let get_node = |id| egraph[id].nodes[0].clone();
+            let actual = get_node(id).build_recexpr(get_node).to_string();
+            let expected = case
+                .expected
+                .unwrap_or_else(|_| panic!("expect error, but got: {actual:?}"))
+                .parse::<RecExpr>()
+                .unwrap()
+                .to_string();
+            assert_eq!(actual, expected);
+        }
+    }
+}
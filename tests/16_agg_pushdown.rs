@@ -0,0 +1,88 @@
+use egg::Searcher;
+use sql_optimizer_labs::{cost::CostFn, expr, plan, RecExpr, Rewrite};
+
+fn rules() -> Vec<Rewrite> {
+    let mut rules = vec![];
+    rules.extend(expr::rules());
+    rules.extend(plan::rules());
+    rules
+}
+
+egg::test_fn! {
+    pushdown_agg_join_left,
+    rules(),
+    // SELECT f.k, sum(f.v) FROM f JOIN d ON f.k = d.k GROUP BY f.k
+    "(agg (list (sum f.v)) (list f.k)
+        (join inner (= f.k d.k)
+            (scan f (list f.k f.v))
+            (scan d (list d.k d.name))))" =>
+    "(agg-final (list (sum f.v)) (list f.k)
+        (join inner (= f.k d.k)
+            (agg (list (sum f.v)) (list f.k)
+                (scan f (list f.k f.v)))
+            (scan d (list d.k d.name))))",
+}
+
+egg::test_fn! {
+    pushdown_agg_join_right,
+    rules(),
+    // SELECT f.k, d.name, avg(f.v) FROM d JOIN f ON d.k = f.k GROUP BY f.k, d.name
+    "(agg (list (avg f.v)) (list f.k d.name)
+        (join inner (= d.k f.k)
+            (scan d (list d.k d.name))
+            (scan f (list f.k f.v))))" =>
+    "(agg-final (list (avg f.v)) (list f.k d.name)
+        (join inner (= d.k f.k)
+            (scan d (list d.k d.name))
+            (agg (list (sum f.v) (count f.v)) (list f.k)
+                (scan f (list f.k f.v)))))",
+}
+
+#[test]
+fn join_keys_not_in_groupby() {
+    // SELECT d.name, sum(f.v) FROM f JOIN d ON f.k = d.k GROUP BY d.name
+    let plan = "
+        (agg (list (sum f.v)) (list d.name)
+            (join inner (= f.k d.k)
+                (scan f (list f.k f.v))
+                (scan d (list d.k d.name))))";
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&rules());
+    let final_agg: egg::Pattern<_> = "(agg-final ?aggs ?groupby ?child)".parse().unwrap();
+    assert!(final_agg.search(&runner.egraph).is_empty());
+}
+
+#[test]
+fn eager_agg_is_cheaper() {
+    // SELECT f.k, count(*) FROM f JOIN d ON f.k = d.k GROUP BY f.k
+    test(
+        "
+        (agg (list rowcount) (list f.k)
+            (join inner (= f.k d.k)
+                (scan f (list f.k))
+                (scan d (list d.k))))",
+        "
+        (agg-final (list rowcount) (list f.k)
+            (hashjoin inner (list f.k) (list d.k)
+                (agg (list rowcount) (list f.k)
+                    (scan f (list f.k)))
+                (scan d (list d.k))))",
+    );
+}
+
+#[track_caller]
+fn test(input: &str, expected: &str) {
+    let runner = egg::Runner::default()
+        .with_expr(&input.parse().unwrap())
+        .run(&rules());
+    let extractor = egg::Extractor::new(
+        &runner.egraph,
+        CostFn {
+            egraph: &runner.egraph,
+        },
+    );
+    let (_, actual) = extractor.find_best(runner.roots[0]);
+    let expected = expected.parse::<RecExpr>().unwrap();
+    assert_eq!(actual.to_string(), expected.to_string());
+}
@@ -0,0 +1,76 @@
+use egg::Searcher;
+use sql_optimizer_labs::{plan::two_phase_agg_rules, EGraph, RecExpr};
+
+egg::test_fn! {
+    split_agg,
+    two_phase_agg_rules(),
+    // SELECT k, sum(a), count(a), max(b), count(*) FROM t GROUP BY k
+    "(agg (list (sum a) (count a) (max b) rowcount) (list k)
+        (scan t (list k a b)))" =>
+    "(agg-final (list (sum a) (count a) (max b) rowcount) (list k)
+        (agg (list (sum a) (count a) (max b) rowcount) (list k)
+            (scan t (list k a b))))",
+}
+
+egg::test_fn! {
+    split_avg,
+    two_phase_agg_rules(),
+    // SELECT avg(a), count(a) FROM t
+    "(agg (list (avg a) (count a)) list
+        (scan t (list a)))" =>
+    "(agg-final (list (avg a) (count a)) list
+        (agg (list (sum a) (count a)) list
+            (scan t (list a))))",
+}
+
+#[test]
+fn schema_is_unchanged() {
+    let plan = "
+        (agg (list (avg a)) (list k)
+            (scan t (list k a)))";
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&two_phase_agg_rules());
+    let egraph = &runner.egraph;
+    let root = runner.roots[0];
+    let schema = |egraph: &EGraph, exprs: &[&str]| -> Vec<_> {
+        exprs
+            .iter()
+            .map(|e| egraph.lookup_expr(&e.parse::<RecExpr>().unwrap()).unwrap())
+            .collect()
+    };
+    assert_eq!(
+        egraph[root].data.schema,
+        Some(schema(egraph, &["(avg a)", "k"]))
+    );
+    let partial: egg::Pattern<_> = "(agg-final ?aggs ?groupby ?partial)".parse().unwrap();
+    let matches = partial.search_eclass(egraph, root).unwrap();
+    let partial = matches.substs[0]["?partial".parse::<egg::Var>().unwrap()];
+    assert_eq!(
+        egraph[partial].data.schema,
+        Some(schema(egraph, &["(sum a)", "(count a)", "k"]))
+    );
+}
+
+#[test]
+fn distinct_agg_not_split() {
+    let plan = "
+        (agg (list (count-distinct a)) list
+            (scan t (list a)))";
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&two_phase_agg_rules());
+    let final_agg: egg::Pattern<_> = "(agg-final ?aggs ?groupby ?child)".parse().unwrap();
+    assert!(final_agg.search(&runner.egraph).is_empty());
+}
+
+#[test]
+fn non_list_aggs_not_split() {
+    // an unvalidated plan whose aggregations are not a list
+    let plan = "(agg (sum a) list (scan t (list a)))";
+    let runner = egg::Runner::default()
+        .with_expr(&plan.parse().unwrap())
+        .run(&two_phase_agg_rules());
+    let final_agg: egg::Pattern<_> = "(agg-final ?aggs ?groupby ?child)".parse().unwrap();
+    assert!(final_agg.search(&runner.egraph).is_empty());
+}
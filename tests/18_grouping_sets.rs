@@ -0,0 +1,154 @@
+use egg::Language;
+use sql_optimizer_labs::{
+    agg::{plan_select, Error},
+    EGraph, RecExpr,
+};
+
+#[test]
+fn rollup() {
+    // SELECT region, city, sum(v) FROM t GROUP BY ROLLUP(region, city)
+    test(Case {
+        select: "(list region city (sum v))",
+        from: "(scan t (list region city v))",
+        where_: "",
+        having: "",
+        groupby: "(list (rollup (list region city)))",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` region) (` city) (` (sum v)))
+            (order list
+                (filter true
+                    (agg (list (sum v))
+                        (grouping-sets (list (list region city) (list region) list))
+                        (filter true
+                            (scan t (list region city v))
+        )))))"),
+    });
+}
+
+#[test]
+fn cube_with_grouping() {
+    // SELECT a, b, grouping(a), count(*) FROM t GROUP BY CUBE(a, b)
+    test(Case {
+        select: "(list a b (grouping a) rowcount)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "(list (cube (list a b)))",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` a) (` b) (` (grouping a)) (` rowcount))
+            (order list
+                (filter true
+                    (agg (list (grouping a) rowcount)
+                        (grouping-sets (list (list a b) (list a) (list b) list))
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
+#[test]
+fn grouping_sets_with_keys() {
+    // SELECT a, b, c, max(d) FROM t GROUP BY a, GROUPING SETS ((b), (c))
+    test(Case {
+        select: "(list a b c (max d))",
+        from: "(scan t (list a b c d))",
+        where_: "",
+        having: "",
+        groupby: "(list a (grouping-sets (list (list b) (list c))))",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` a) (` b) (` c) (` (max d)))
+            (order list
+                (filter true
+                    (agg (list (max d))
+                        (grouping-sets (list (list a b) (list a c)))
+                        (filter true
+                            (scan t (list a b c d))
+        )))))"),
+    });
+}
+
+#[test]
+fn error_grouping_not_in_groupby() {
+    // SELECT grouping(b) FROM t GROUP BY ROLLUP(a)
+    test(Case {
+        select: "(list (grouping b))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "(list (rollup (list a)))",
+        orderby: "",
+        expected: Err(Error::GroupingNotInGroupBy("b".into())),
+    });
+}
+
+#[test]
+fn error_nested_grouping_sets() {
+    // SELECT a FROM t GROUP BY ROLLUP(a, CUBE(b))
+    test(Case {
+        select: "(list a)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "(list (rollup (list a (cube (list b)))))",
+        orderby: "",
+        expected: Err(Error::NestedGroupingSets),
+    });
+}
+
+struct Case {
+    select: &'static str,
+    from: &'static str,
+    where_: &'static str,
+    having: &'static str,
+    groupby: &'static str,
+    orderby: &'static str,
+    expected: Result<&'static str, Error>,
+}
+
+#[track_caller]
+fn test(mut case: Case) {
+    if case.where_.is_empty() {
+        case.where_ = "true";
+    }
+    if case.having.is_empty() {
+        case.having = "true";
+    }
+    if case.groupby.is_empty() {
+        case.groupby = "list";
+    }
+    if case.orderby.is_empty() {
+        case.orderby = "list";
+    }
+    let mut egraph = EGraph::default();
+    let projection = egraph.add_expr(&case.select.parse().unwrap());
+    let from = egraph.add_expr(&case.from.parse().unwrap());
+    let where_ = egraph.add_expr(&case.where_.parse().unwrap());
+    let having = egraph.add_expr(&case.having.parse().unwrap());
+    let groupby = egraph.add_expr(&case.groupby.parse().unwrap());
+    let orderby = egraph.add_expr(&case.orderby.parse().unwrap());
+    match plan_select(
+        &mut egraph,
+        from,
+        where_,
+        having,
+        groupby,
+        orderby,
+        projection,
+    ) {
+        Err(e) => assert_eq!(case.expected, Err(e)),
+        Ok(id) => {
+            let get_node = |id| egraph[id].nodes[0].clone();
+            let actual = get_node(id).build_recexpr(get_node).to_string();
+            let expected = case
+                .expected
+                .unwrap_or_else(|_| panic!("expect error, but got: {actual:?}"))
+                .parse::<RecExpr>()
+                .unwrap()
+                .to_string();
+            assert_eq!(actual, expected);
+        }
+    }
+}
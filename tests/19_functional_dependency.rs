@@ -0,0 +1,174 @@
+use egg::Language;
+use sql_optimizer_labs::{
+    agg::{plan_select, Error},
+    plan::rules,
+    EGraph, ExprAnalysis, RecExpr,
+};
+
+#[test]
+fn column_determined_by_primary_key() {
+    // SELECT t.id, t.name, count(*) FROM t GROUP BY t.id
+    test(Case {
+        select: "(list t.id t.name rowcount)",
+        from: "(scan t (list t.id t.name))",
+        where_: "",
+        having: "",
+        groupby: "(list t.id)",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` t.id) (` (max t.name)) (` rowcount))
+            (order list
+                (filter true
+                    (agg (list rowcount (max t.name)) (list t.id)
+                        (filter true
+                            (scan t (list t.id t.name))
+        )))))"),
+    });
+}
+
+#[test]
+fn drop_redundant_group_keys() {
+    // SELECT t.name, sum(s.v) FROM t JOIN s ON t.id = s.t_id GROUP BY t.id, t.name
+    test(Case {
+        select: "(list t.name (sum s.v))",
+        from: "
+        (join inner (= t.id s.t_id)
+            (scan t (list t.id t.name))
+            (scan s (list s.t_id s.v)))",
+        where_: "",
+        having: "",
+        groupby: "(list t.id t.name)",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` (max t.name)) (` (sum s.v)))
+            (order list
+                (filter true
+                    (agg (list (sum s.v) (max t.name)) (list t.id)
+                        (filter true
+                            (join inner (= t.id s.t_id)
+                                (scan t (list t.id t.name))
+                                (scan s (list s.t_id s.v)))
+        )))))"),
+    });
+}
+
+#[test]
+fn error_column_not_determined() {
+    // SELECT s.v FROM t JOIN s ON t.id = s.t_id GROUP BY t.id
+    test(Case {
+        select: "(list s.v)",
+        from: "
+        (join inner (= t.id s.t_id)
+            (scan t (list t.id t.name))
+            (scan s (list s.t_id s.v)))",
+        where_: "",
+        having: "",
+        groupby: "(list t.id)",
+        orderby: "",
+        expected: Err(Error::ColumnNotInAgg("s.v".into())),
+    });
+}
+
+#[test]
+fn distinct_on_primary_key() {
+    // SELECT DISTINCT t.id, t.name FROM t
+    assert!(is_eliminated(
+        "(distinct (scan t (list t.id t.name)))",
+        "(scan t (list t.id t.name))",
+    ));
+    // SELECT DISTINCT t.name FROM t
+    assert!(!is_eliminated(
+        "(distinct (proj (list t.name) (scan t (list t.id t.name))))",
+        "(proj (list t.name) (scan t (list t.id t.name)))",
+    ));
+}
+
+#[test]
+fn distinct_on_join() {
+    // SELECT DISTINCT * FROM t JOIN u ON t.id = u.id
+    assert!(is_eliminated(
+        "
+        (distinct
+            (join inner (= t.id u.id)
+                (scan t (list t.id t.name))
+                (scan u (list u.id u.v))))",
+        "
+        (join inner (= t.id u.id)
+            (scan t (list t.id t.name))
+            (scan u (list u.id u.v)))",
+    ));
+}
+
+/// Returns a catalog where `t.id`, `s.id` and `u.id` are the primary keys.
+fn analysis() -> ExprAnalysis {
+    let mut analysis = ExprAnalysis::default();
+    for table in ["t", "s", "u"] {
+        let key = format!("{table}.id");
+        (analysis.primary_keys).insert(table.into(), vec![key.into()]);
+    }
+    analysis
+}
+
+/// Returns true if `input` is optimized to be equivalent to `output`.
+fn is_eliminated(input: &str, output: &str) -> bool {
+    let runner = egg::Runner::<_, _, ()>::new(analysis())
+        .with_expr(&input.parse().unwrap())
+        .run(&rules());
+    let output = runner.egraph.lookup_expr(&output.parse().unwrap());
+    output == Some(runner.egraph.find(runner.roots[0]))
+}
+
+struct Case {
+    select: &'static str,
+    from: &'static str,
+    where_: &'static str,
+    having: &'static str,
+    groupby: &'static str,
+    orderby: &'static str,
+    expected: Result<&'static str, Error>,
+}
+
+#[track_caller]
+fn test(mut case: Case) {
+    if case.where_.is_empty() {
+        case.where_ = "true";
+    }
+    if case.having.is_empty() {
+        case.having = "true";
+    }
+    if case.groupby.is_empty() {
+        case.groupby = "list";
+    }
+    if case.orderby.is_empty() {
+        case.orderby = "list";
+    }
+    let mut egraph = EGraph::new(analysis());
+    let projection = egraph.add_expr(&case.select.parse().unwrap());
+    let from = egraph.add_expr(&case.from.parse().unwrap());
+    let where_ = egraph.add_expr(&case.where_.parse().unwrap());
+    let having = egraph.add_expr(&case.having.parse().unwrap());
+    let groupby = egraph.add_expr(&case.groupby.parse().unwrap());
+    let orderby = egraph.add_expr(&case.orderby.parse().unwrap());
+    match plan_select(
+        &mut egraph,
+        from,
+        where_,
+        having,
+        groupby,
+        orderby,
+        projection,
+    ) {
+        Err(e) => assert_eq!(case.expected, Err(e)),
+        Ok(id) => {
+            let get_node = |id| egraph[id].nodes[0].clone();
+            let actual = get_node(id).build_recexpr(get_node).to_string();
+            let expected = case
+                .expected
+                .unwrap_or_else(|_| panic!("expect error, but got: {actual:?}"))
+                .parse::<RecExpr>()
+                .unwrap()
+                .to_string();
+            assert_eq!(actual, expected);
+        }
+    }
+}
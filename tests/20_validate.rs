@@ -0,0 +1,156 @@
+use sql_optimizer_labs::validate::{validate, PlanError};
+
+#[test]
+fn valid_plans() {
+    for plan in [
+        "(+ a 1)",
+        "
+        (proj (list (+ (` (sum (+ a b))) (` (+ a 1))))
+            (order (list (asc (` (max b))))
+                (filter (> (` (count a)) 1)
+                    (agg (list (sum (+ a b)) (count a) (max b)) (list (+ a 1))
+                        (filter (> b 1)
+                            (scan t (list a b))
+        )))))",
+        "
+        (proj (list t1.a t2.b)
+            (hashjoin inner (list t1.id) (list t2.id)
+                (scan t1 (list t1.id t1.a))
+                (limit 10 0 (scan t2 (list t2.id t2.b)))
+        ))",
+        "
+        (proj (list a (` (over row_number (list a) (list (asc b)))))
+            (window (list (over row_number (list a) (list (asc b))))
+                (scan t (list a b))
+        ))",
+        "
+        (agg (list (max d)) (grouping-sets (list (list a b) (list a c)))
+            (union_all
+                (scan t1 (list a b c d))
+                (values (list 1 2 3 4) (list 5 6 7 8))
+        ))",
+    ] {
+        assert_eq!(validate(&plan.parse().unwrap()), Ok(()), "{plan}");
+    }
+}
+
+#[test]
+fn expect_list() {
+    test(
+        "(proj a (scan t b))",
+        PlanError::ExpectList {
+            path: vec![1, 1],
+            found: "b".into(),
+        },
+    );
+}
+
+#[test]
+fn expect_plan() {
+    test(
+        "(filter (= a 1) (list a))",
+        PlanError::ExpectPlan {
+            path: vec![1],
+            found: "(list a)".into(),
+        },
+    );
+}
+
+#[test]
+fn expect_expr() {
+    test(
+        "(filter (scan t (list a)) (scan t (list a)))",
+        PlanError::ExpectExpr {
+            path: vec![0],
+            found: "(scan t (list a))".into(),
+        },
+    );
+}
+
+#[test]
+fn expect_join_type() {
+    test(
+        "(join true true (scan t1 (list a)) (scan t2 (list b)))",
+        PlanError::ExpectJoinType {
+            path: vec![0],
+            found: "true".into(),
+        },
+    );
+}
+
+#[test]
+fn join_keys_mismatch() {
+    test(
+        "
+        (hashjoin inner (list a) (list b c)
+            (scan t1 (list a))
+            (scan t2 (list b c)))",
+        PlanError::LengthMismatch {
+            path: vec![2],
+            expected: 1,
+            found: 2,
+        },
+    );
+}
+
+#[test]
+fn set_operation_mismatch() {
+    test(
+        "(union_all (scan t1 (list a)) (scan t2 (list b c)))",
+        PlanError::LengthMismatch {
+            path: vec![1],
+            expected: 1,
+            found: 2,
+        },
+    );
+}
+
+#[test]
+fn column_not_found() {
+    test(
+        "
+        (proj (list (+ a 1))
+            (filter (= c 1)
+                (scan t (list a b))))",
+        PlanError::ColumnNotFound {
+            path: vec![1, 0, 0],
+            column: "c".into(),
+        },
+    );
+    test(
+        "
+        (proj (list (` (sum b)))
+            (agg (list (count b)) (list a)
+                (scan t (list a b))))",
+        PlanError::ColumnNotFound {
+            path: vec![0, 0],
+            column: "(sum b)".into(),
+        },
+    );
+}
+
+#[test]
+fn empty_plan() {
+    let plan = sql_optimizer_labs::RecExpr::default();
+    assert_eq!(validate(&plan), Err(PlanError::Empty));
+    let error = sql_optimizer_labs::Optimizer::new()
+        .optimize(&plan)
+        .unwrap_err();
+    assert_eq!(error, PlanError::Empty.into());
+}
+
+#[test]
+fn display() {
+    let error = validate(&"(filter (= c 1) (scan t (list a b)))".parse().unwrap()).unwrap_err();
+    assert_eq!(error.to_string(), "column c not found at [0, 0]");
+    let error = sql_optimizer_labs::Error::from(error);
+    assert_eq!(
+        error.to_string(),
+        "invalid plan: column c not found at [0, 0]"
+    );
+}
+
+#[track_caller]
+fn test(plan: &str, expected: PlanError) {
+    assert_eq!(validate(&plan.parse().unwrap()), Err(expected));
+}
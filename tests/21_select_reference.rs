@@ -0,0 +1,170 @@
+use egg::Language;
+use sql_optimizer_labs::{
+    agg::{plan_select, Error},
+    EGraph, RecExpr,
+};
+
+#[test]
+fn order_by_alias_and_ordinal() {
+    // SELECT a, sum(b) AS total FROM t GROUP BY a ORDER BY total DESC, 1
+    test(Case {
+        select: "(list a (as (sum b) total))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "(list a)",
+        orderby: "(list (desc total) 1)",
+        expected: Ok("
+        (proj (list (` a) (` (sum b)))
+            (order (list (desc (` (sum b))) (` a))
+                (filter true
+                    (agg (list (sum b)) (list a)
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
+#[test]
+fn having_alias() {
+    // SELECT a, count(*) AS n FROM t GROUP BY a HAVING n > 1
+    test(Case {
+        select: "(list a (as rowcount n))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "(> n 1)",
+        groupby: "(list a)",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` a) (` rowcount))
+            (order list
+                (filter (> (` rowcount) 1)
+                    (agg (list rowcount) (list a)
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
+#[test]
+fn having_prefers_input_column() {
+    // SELECT a + 1 AS b FROM t HAVING b > 1
+    test(Case {
+        select: "(list (as (+ a 1) b))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "(> b 1)",
+        groupby: "",
+        orderby: "(list b)",
+        expected: Ok("
+        (proj (list (+ a 1))
+            (order (list (+ a 1))
+                (filter (> b 1)
+                    (filter true
+                        (scan t (list a b))
+        ))))"),
+    });
+}
+
+#[test]
+fn order_by_column_not_in_select() {
+    // SELECT a FROM t ORDER BY b
+    test(Case {
+        select: "(list a)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "(list (asc b))",
+        expected: Ok("
+        (proj (list a)
+            (order (list (asc b))
+                (filter true
+                    (filter true
+                        (scan t (list a b))
+        ))))"),
+    });
+}
+
+#[test]
+fn error_ordinal_out_of_range() {
+    // SELECT a FROM t ORDER BY 2
+    test(Case {
+        select: "(list a)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "(list (asc 2))",
+        expected: Err(Error::OrdinalOutOfRange(2)),
+    });
+}
+
+#[test]
+fn error_ambiguous_alias() {
+    // SELECT a AS x, b AS x FROM t ORDER BY x
+    test(Case {
+        select: "(list (as a x) (as b x))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "(list x)",
+        expected: Err(Error::AmbiguousAlias("x".into())),
+    });
+}
+
+struct Case {
+    select: &'static str,
+    from: &'static str,
+    where_: &'static str,
+    having: &'static str,
+    groupby: &'static str,
+    orderby: &'static str,
+    expected: Result<&'static str, Error>,
+}
+
+#[track_caller]
+fn test(mut case: Case) {
+    if case.where_.is_empty() {
+        case.where_ = "true";
+    }
+    if case.having.is_empty() {
+        case.having = "true";
+    }
+    if case.groupby.is_empty() {
+        case.groupby = "list";
+    }
+    if case.orderby.is_empty() {
+        case.orderby = "list";
+    }
+    let mut egraph = EGraph::default();
+    let projection = egraph.add_expr(&case.select.parse().unwrap());
+    let from = egraph.add_expr(&case.from.parse().unwrap());
+    let where_ = egraph.add_expr(&case.where_.parse().unwrap());
+    let having = egraph.add_expr(&case.having.parse().unwrap());
+    let groupby = egraph.add_expr(&case.groupby.parse().unwrap());
+    let orderby = egraph.add_expr(&case.orderby.parse().unwrap());
+    match plan_select(
+        &mut egraph,
+        from,
+        where_,
+        having,
+        groupby,
+        orderby,
+        projection,
+    ) {
+        Err(e) => assert_eq!(case.expected, Err(e)),
+        Ok(id) => {
+            let get_node = |id| egraph[id].nodes[0].clone();
+            let actual = get_node(id).build_recexpr(get_node).to_string();
+            let expected = case
+                .expected
+                .unwrap_or_else(|_| panic!("expect error, but got: {actual:?}"))
+                .parse::<RecExpr>()
+                .unwrap()
+                .to_string();
+            assert_eq!(actual, expected);
+        }
+    }
+}
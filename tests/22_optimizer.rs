@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use sql_optimizer_labs::{
+    optimizer::{Extraction, RuleGroup},
+    validate::PlanError,
+    Error, Optimizer, Stage,
+};
+
+// SELECT s.name, e.cid
+// FROM student AS s, enrolled AS e
+// WHERE s.sid = e.sid AND e.grade = 'A'
+const PLAN: &str = "
+    (proj (list s.name e.cid)
+    (filter (and (= s.sid e.sid) (= e.grade 'A'))
+    (join inner true
+        (scan s (list s.sid s.name))
+        (scan e (list e.sid e.cid e.grade))
+    )))";
+
+#[test]
+fn optimize() {
+    let (best, report) = Optimizer::new().optimize(&PLAN.parse().unwrap()).unwrap();
+    assert_eq!(
+        best.to_string(),
+        "
+        (proj (list s.name e.cid)
+        (hashjoin inner (list s.sid) (list e.sid)
+            (scan s (list s.sid s.name))
+            (filter (= e.grade 'A')
+                (scan e (list e.sid e.cid e.grade)))
+        ))"
+        .parse::<sql_optimizer_labs::RecExpr>()
+        .unwrap()
+        .to_string()
+    );
+    let stages: Vec<_> = report.stages.iter().map(|s| s.name).collect();
+    assert_eq!(
+        stages,
+        [
+            "expr",
+            "predicate-pushdown",
+            "join-reorder",
+            "projection-pushdown"
+        ]
+    );
+    assert!(report.iterations() > 0);
+    for stage in report.stages.iter() {
+        assert!(stage.egraph_nodes >= stage.egraph_classes);
+    }
+    assert_eq!(report.cost_after, report.stages.last().unwrap().cost);
+    assert!(report.cost_after < report.cost_before);
+}
+
+#[test]
+fn rule_groups() {
+    let (best, report) = Optimizer::new()
+        .rule_groups(&[RuleGroup::Expr, RuleGroup::PredicatePushdown])
+        .optimize(&PLAN.parse().unwrap())
+        .unwrap();
+    assert_eq!(
+        best.to_string(),
+        "
+        (proj (list s.name e.cid)
+        (join inner (= e.sid s.sid)
+            (scan s (list s.sid s.name))
+            (filter (= e.grade 'A')
+                (scan e (list e.sid e.cid e.grade)))
+        ))"
+        .parse::<sql_optimizer_labs::RecExpr>()
+        .unwrap()
+        .to_string()
+    );
+    // stages without enabled rules are skipped
+    let stages: Vec<_> = report.stages.iter().map(|s| s.name).collect();
+    assert_eq!(stages, ["expr", "predicate-pushdown", "join-reorder"]);
+
+    let (best, _) = Optimizer::new()
+        .disable(RuleGroup::PredicatePushdown)
+        .disable(RuleGroup::Join)
+        .optimize(&PLAN.parse().unwrap())
+        .unwrap();
+    assert!(best.to_string().contains("(join inner true"));
+}
+
+#[test]
+fn limits() {
+    let (_, report) = Optimizer::new()
+        .iter_limit(1)
+        .optimize(&PLAN.parse().unwrap())
+        .unwrap();
+    for stage in report.stages.iter() {
+        assert_eq!(stage.iterations, 1);
+    }
+    assert!(matches!(
+        report.stages[0].stop_reason,
+        egg::StopReason::IterationLimit(1)
+    ));
+
+    let (_, report) = Optimizer::new()
+        .node_limit(20)
+        .time_limit(Duration::from_secs(60))
+        .optimize(&PLAN.parse().unwrap())
+        .unwrap();
+    assert!(matches!(
+        report.stages[0].stop_reason,
+        egg::StopReason::NodeLimit(_)
+    ));
+}
+
+#[test]
+fn staged_pipeline() {
+    // SELECT t1.a, t4.d FROM t1, t2, t3, t4
+    // WHERE t1.id = t2.id AND t2.k = t3.k AND t3.id = t4.id AND t1.a = 1
+    let plan = "
+        (proj (list t1.a t4.d)
+        (filter (and (and (and (= t1.id t2.id) (= t2.k t3.k)) (= t3.id t4.id)) (= t1.a 1))
+        (join inner true
+            (join inner true
+                (join inner true
+                    (scan t1 (list t1.id t1.a t1.b))
+                    (scan t2 (list t2.id t2.k t2.b)))
+                (scan t3 (list t3.id t3.k t3.c)))
+            (scan t4 (list t4.id t4.d t4.e))
+        )))"
+    .parse()
+    .unwrap();
+    let (_, staged) = Optimizer::new().optimize(&plan).unwrap();
+    let (_, single) = Optimizer::new()
+        .stages(vec![Stage::new("all", &RuleGroup::ALL)])
+        .optimize(&plan)
+        .unwrap();
+    let max_nodes = |report: &sql_optimizer_labs::OptimizeReport| {
+        (report.stages.iter())
+            .map(|s| s.egraph_nodes)
+            .max()
+            .unwrap()
+    };
+    assert!(max_nodes(&staged) < max_nodes(&single));
+    assert!(staged.cost_after <= single.cost_after);
+}
+
+#[test]
+fn join_order() {
+    // SELECT t0.v FROM t0, t1, ..., t5 WHERE t0.k = t1.k AND t1.k = t2.k AND ...
+    let scan = |i: usize| format!("(scan t{i} (list t{i}.k t{i}.v))");
+    let join = (1..6).fold(scan(0), |left, i| {
+        format!("(join inner true {left} {})", scan(i))
+    });
+    let cond = (1..6)
+        .map(|i| format!("(= t{}.k t{i}.k)", i - 1))
+        .reduce(|a, b| format!("(and {a} {b})"))
+        .unwrap();
+    let plan = format!("(proj (list t0.v) (filter {cond} {join}))")
+        .parse()
+        .unwrap();
+    let (best, ordered) = Optimizer::new().optimize(&plan).unwrap();
+    let stages = (Stage::default_pipeline().into_iter())
+        .map(|stage| Stage {
+            join_order: false,
+            ..stage
+        })
+        .collect();
+    let (_, unordered) = Optimizer::new().stages(stages).optimize(&plan).unwrap();
+    let max_nodes = |report: &sql_optimizer_labs::OptimizeReport| {
+        (report.stages.iter())
+            .map(|s| s.egraph_nodes)
+            .max()
+            .unwrap()
+    };
+    // the rules start from a good order instead of exploring all of them
+    assert!(max_nodes(&ordered) * 10 < max_nodes(&unordered));
+    assert!(ordered.cost_after <= unordered.cost_after);
+    assert!(!best.to_string().contains("(join"));
+}
+
+#[test]
+fn prune_columns_under_nested_loop_join() {
+    // SELECT t1.b FROM t1 JOIN t2 ON t1.a > t2.x
+    let plan = "
+        (proj (list t1.b)
+        (join inner (> t1.a t2.x)
+            (scan t1 (list t1.a t1.b t1.c))
+            (scan t2 (list t2.x t2.y))
+        ))";
+    let (best, _) = Optimizer::new().optimize(&plan.parse().unwrap()).unwrap();
+    let best = best.to_string();
+    assert!(best.contains("(nljoin "), "{best}");
+    assert!(best.contains("(scan t1 (list t1.a t1.b))"), "{best}");
+    assert!(best.contains("(scan t2 (list t2.x))"), "{best}");
+}
+
+#[test]
+fn ast_size_extraction() {
+    let (best, report) = Optimizer::new()
+        .rule_groups(&[RuleGroup::Expr])
+        .extraction(Extraction::AstSize)
+        .optimize(&"(proj (list (+ a 0)) (scan t (list a)))".parse().unwrap())
+        .unwrap();
+    assert_eq!(best.to_string(), "(proj (list a) (scan t (list a)))");
+    assert_eq!(report.cost_before, 9.0);
+    assert_eq!(report.cost_after, 7.0);
+}
+
+#[test]
+fn invalid_plan() {
+    let error = Optimizer::new()
+        .optimize(&"(filter (= a 1) (list a))".parse().unwrap())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        Error::Plan(PlanError::ExpectPlan {
+            path: vec![1],
+            found: "(list a)".into(),
+        })
+    );
+}
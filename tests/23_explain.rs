@@ -0,0 +1,93 @@
+use sql_optimizer_labs::{explain::Explain, RecExpr};
+
+fn plan() -> RecExpr {
+    "
+    (proj (list t1.a (` (sum t2.b)))
+        (order (list (desc (` (sum t2.b))))
+            (agg (list (sum t2.b)) (list t1.a)
+                (hashjoin inner (list t1.id) (list t2.id)
+                    (filter (> t1.a 1) (scan t1 (list t1.id t1.a)))
+                    (scan t2 (list t2.id t2.b))
+    ))))"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn explain() {
+    assert_eq!(
+        Explain::new(&plan()).to_string(),
+        "\
+Projection [t1.a, sum(t2.b)]
+  Order [sum(t2.b) desc]
+    Aggregate [sum(t2.b)] group by [t1.a]
+      HashJoin inner on [t1.id = t2.id]
+        Filter t1.a > 1
+          Scan t1 [id, a]
+        Scan t2 [id, b]
+"
+    );
+}
+
+#[test]
+fn rows_and_cost() {
+    assert_eq!(
+        Explain::new(&plan()).with_rows().with_cost().to_string(),
+        "\
+Projection [t1.a, sum(t2.b)] (rows=1000, cost=118028.92)
+  Order [sum(t2.b) desc] (rows=1000, cost=113028.92)
+    Aggregate [sum(t2.b)] group by [t1.a] (rows=1000, cost=63200)
+      HashJoin inner on [t1.id = t2.id] (rows=10000, cost=13200)
+        Filter t1.a > 1 (rows=100, cost=6000)
+          Scan t1 [id, a] (rows=1000, cost=3000)
+        Scan t2 [id, b] (rows=1000, cost=3000)
+"
+    );
+}
+
+#[test]
+fn verbose() {
+    assert_eq!(
+        Explain::new(&plan()).with_schema().verbose().to_string(),
+        "\
+Projection [#1, #0] (schema=[t1.a, sum(t2.b)])
+  Order [#0 desc] (schema=[sum(t2.b), t1.a])
+    Aggregate [sum(#3)] group by [#1] (schema=[sum(t2.b), t1.a])
+      HashJoin inner on [#0 = #0] (schema=[t1.id, t1.a, t2.id, t2.b])
+        Filter #1 > 1 (schema=[t1.id, t1.a])
+          Scan t1 [id, a] (schema=[t1.id, t1.a])
+        Scan t2 [id, b] (schema=[t2.id, t2.b])
+"
+    );
+}
+
+#[test]
+fn expressions() {
+    let plan = "
+    (proj (list (over rank (list a) (list (asc b))) (+ a (* b 2)))
+        (window (list (over rank (list a) (list (asc b))))
+            (union_all
+                (values (list 1 2) (list 3 4))
+                (limit 10 0 (scan t (list a b))))))"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Explain::new(&plan).to_string(),
+        "\
+Projection [rank() over (partition by [a] order by [b asc]), a + (b * 2)]
+  Window [rank() over (partition by [a] order by [b asc])]
+    UnionAll
+      Values [[1, 2], [3, 4]]
+      Limit 10 offset 0
+        Scan t [a, b]
+"
+    );
+    // an expression is rendered in one line
+    let expr = "(and (not (isnull a)) (= (count-distinct b) rowcount))"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Explain::new(&expr).to_string(),
+        "not a is null and (count(distinct b) = count(*))\n"
+    );
+}
@@ -0,0 +1,130 @@
+use sql_optimizer_labs::{export::*, EGraph, RecExpr};
+
+#[test]
+fn plan_dot() {
+    let plan: RecExpr = "(filter (= a 'x\"') (scan t (list a)))".parse().unwrap();
+    assert_eq!(
+        plan_to_dot(&plan),
+        r#"digraph plan {
+  node [shape=box];
+  n0 [label="a"];
+  n1 [label="'x\"'"];
+  n2 [label="="];
+  n3 [label="t"];
+  n4 [label="a"];
+  n5 [label="list"];
+  n6 [label="scan"];
+  n7 [label="filter"];
+  n2 -> n0 [label=0];
+  n2 -> n1 [label=1];
+  n5 -> n4 [label=0];
+  n6 -> n3 [label=0];
+  n6 -> n5 [label=1];
+  n7 -> n2 [label=0];
+  n7 -> n6 [label=1];
+}
+"#
+    );
+}
+
+#[test]
+fn plan_json() {
+    let plan: RecExpr = "(filter (= a 'x\"') (scan t (list a)))".parse().unwrap();
+    assert_eq!(
+        plan_to_json(&plan),
+        concat!(
+            r#"{"op": "filter", "children": ["#,
+            r#"{"op": "=", "children": [{"op": "a", "children": []}, {"op": "'x\"'", "children": []}]}, "#,
+            r#"{"op": "scan", "children": ["#,
+            r#"{"op": "t", "children": []}, "#,
+            r#"{"op": "list", "children": [{"op": "a", "children": []}]}]}]}"#,
+        )
+    );
+}
+
+fn egraph() -> EGraph {
+    let mut egraph = EGraph::default();
+    egraph.add_expr(&"(proj (list (+ 1 1)) (scan t (list a)))".parse().unwrap());
+    egraph.rebuild();
+    egraph
+}
+
+#[test]
+fn egraph_dot() {
+    assert_eq!(
+        egraph_to_dot(&egraph()),
+        r##"digraph egraph {
+  compound=true;
+  clusterrank=local;
+  subgraph cluster_0 {
+    style=dotted;
+    label="#0\nconstant: 1";
+    "0.0" [label="1"];
+  }
+  subgraph cluster_1 {
+    style=dotted;
+    label="#1\nconstant: 2";
+    "1.0" [label="2"];
+    "1.1" [label="+"];
+  }
+  subgraph cluster_3 {
+    style=dotted;
+    label="#3\nschema: [#1]";
+    "3.0" [label="list"];
+  }
+  subgraph cluster_4 {
+    style=dotted;
+    label="#4\ncolumns: [t]";
+    "4.0" [label="t"];
+  }
+  subgraph cluster_5 {
+    style=dotted;
+    label="#5\ncolumns: [a]";
+    "5.0" [label="a"];
+  }
+  subgraph cluster_6 {
+    style=dotted;
+    label="#6\ncolumns: [a]\nschema: [#5]";
+    "6.0" [label="list"];
+  }
+  subgraph cluster_7 {
+    style=dotted;
+    label="#7\ncolumns: [a, t]\nschema: [#5]";
+    "7.0" [label="scan"];
+  }
+  subgraph cluster_8 {
+    style=dotted;
+    label="#8\nschema: [#1]";
+    "8.0" [label="proj"];
+  }
+  "1.1" -> "0.0" [lhead=cluster_0, label=0];
+  "1.1" -> "0.0" [lhead=cluster_0, label=1];
+  "3.0" -> "1.0" [lhead=cluster_1, label=0];
+  "6.0" -> "5.0" [lhead=cluster_5, label=0];
+  "7.0" -> "4.0" [lhead=cluster_4, label=0];
+  "7.0" -> "6.0" [lhead=cluster_6, label=1];
+  "8.0" -> "3.0" [lhead=cluster_3, label=0];
+  "8.0" -> "7.0" [lhead=cluster_7, label=1];
+}
+"##
+    );
+}
+
+#[test]
+fn egraph_json() {
+    assert_eq!(
+        egraph_to_json(&egraph()),
+        concat!(
+            r#"{"classes": ["#,
+            r#"{"id": 0, "nodes": [{"op": "1", "children": []}], "data": {"constant": "1", "columns": [], "schema": null}}, "#,
+            r#"{"id": 1, "nodes": [{"op": "2", "children": []}, {"op": "+", "children": [0, 0]}], "data": {"constant": "2", "columns": [], "schema": null}}, "#,
+            r#"{"id": 3, "nodes": [{"op": "list", "children": [1]}], "data": {"constant": null, "columns": [], "schema": [1]}}, "#,
+            r#"{"id": 4, "nodes": [{"op": "t", "children": []}], "data": {"constant": null, "columns": ["t"], "schema": null}}, "#,
+            r#"{"id": 5, "nodes": [{"op": "a", "children": []}], "data": {"constant": null, "columns": ["a"], "schema": null}}, "#,
+            r#"{"id": 6, "nodes": [{"op": "list", "children": [5]}], "data": {"constant": null, "columns": ["a"], "schema": [5]}}, "#,
+            r#"{"id": 7, "nodes": [{"op": "scan", "children": [4, 6]}], "data": {"constant": null, "columns": ["a", "t"], "schema": [5]}}, "#,
+            r#"{"id": 8, "nodes": [{"op": "proj", "children": [3, 7]}], "data": {"constant": null, "columns": [], "schema": [1]}}"#,
+            "]}",
+        )
+    );
+}
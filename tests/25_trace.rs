@@ -0,0 +1,146 @@
+use sql_optimizer_labs::{optimizer::RuleGroup, plan, ExprAnalysis, Optimizer, Stage};
+
+#[test]
+fn trace() {
+    // SELECT s.name, e.cid
+    // FROM student AS s, enrolled AS e
+    // WHERE s.sid = e.sid AND e.grade = 'A'
+    let plan = "
+    (proj (list s.name e.cid)
+    (filter (and (= s.sid e.sid) (= e.grade 'A'))
+    (join inner true
+        (scan s (list s.sid s.name))
+        (scan e (list e.sid e.cid e.grade))
+    )))"
+    .parse()
+    .unwrap();
+    // without ordering the joins by dynamic programming, which solves the query at once
+    let stages = (Stage::default_pipeline().into_iter())
+        .map(|stage| Stage {
+            join_order: false,
+            ..stage
+        })
+        .collect();
+    let (best, report) = Optimizer::new()
+        .stages(stages)
+        .with_trace()
+        .optimize(&plan)
+        .unwrap();
+    let rules: Vec<_> = (report.trace.iter())
+        .map(|step| (step.stage, step.rule.as_str()))
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            ("expr", "and-comm"),
+            ("expr", "eq-comm"),
+            ("predicate-pushdown", "pushdown-filter-join"),
+            ("predicate-pushdown", "and-true"),
+            ("predicate-pushdown", "pushdown-filter-join-right"),
+            ("join-reorder", "eq-comm"),
+            ("join-reorder", "hash-join-on-one-eq"),
+        ]
+    );
+    assert_eq!(
+        report.trace.last().unwrap().plan.to_string(),
+        best.to_string()
+    );
+    assert_eq!(
+        report.trace[4].to_string(),
+        "\
+[predicate-pushdown] pushdown-filter-join-right
+  if columns_is_subset(\"?cond1\", \"?right\")
+  => (proj (list s.name e.cid) (join inner (= e.sid s.sid) (scan s (list s.sid s.name)) (filter (= e.grade 'A') (scan e (list e.sid e.cid e.grade)))))
+"
+    );
+}
+
+#[test]
+fn join_order() {
+    // SELECT s.name, e.cid
+    // FROM student AS s, enrolled AS e
+    // WHERE s.sid = e.sid
+    let plan = "
+    (proj (list s.name e.cid)
+    (filter (= s.sid e.sid)
+    (join inner true
+        (scan s (list s.sid s.name))
+        (scan e (list e.sid e.cid))
+    )))"
+    .parse()
+    .unwrap();
+    let (best, report) = Optimizer::new().with_trace().optimize(&plan).unwrap();
+    // the join order is traced as a single step
+    assert_eq!(
+        report.trace[0].to_string(),
+        "\
+[expr] join-order
+  => (proj (list s.name e.cid) (hashjoin inner (list s.sid) (list e.sid) (scan s (list s.sid s.name)) (scan e (list e.sid e.cid))))
+"
+    );
+    assert_eq!(
+        report.trace.last().unwrap().plan.to_string(),
+        best.to_string()
+    );
+}
+
+#[test]
+fn custom_applier() {
+    // SELECT a FROM t
+    let plan = "(proj (list a) (scan t (list a b)))".parse().unwrap();
+    let (_, report) = Optimizer::new()
+        .stages(vec![Stage::new("proj", &[RuleGroup::ProjectionPushdown])])
+        .with_trace()
+        .optimize(&plan)
+        .unwrap();
+    // the rules with custom appliers are also traced
+    assert_eq!(
+        report.trace_log(),
+        "\
+[proj] pushdown-proj-scan
+  => (proj (list a) (scan t (column-prune (list a) (list a b))))
+[proj] identical-proj
+  if schema_is_eq(\"?exprs\", \"?child\")
+  => (scan t (column-prune (list a) (list a b)))
+[proj] column-prune
+  if is_list(\"?list\")
+  => (scan t (list a))
+"
+    );
+}
+
+#[test]
+fn conditions() {
+    // SELECT * FROM a, b WHERE a.x = b.y
+    let plan = "(join inner (= a.x b.y) (scan a (list a.x)) (scan b (list b.y)))"
+        .parse()
+        .unwrap();
+    let rules = plan::join_rules();
+    let runner = egg::Runner::<_, _, ()>::new(ExprAnalysis::default())
+        .with_explanations_enabled()
+        .with_expr(&plan)
+        .run(&rules);
+    // the conditions are recorded in the egraph when they pass
+    let conditions = &runner.egraph.analysis.conditions;
+    assert_eq!(
+        conditions[&"hash-join-on-one-eq".into()],
+        [
+            "columns_is_subset(\"?el\", \"?left\")",
+            "columns_is_subset(\"?er\", \"?right\")"
+        ]
+    );
+    assert!(!conditions.contains_key(&"nested-loop-join".into()));
+
+    // and only if explanations are enabled
+    let runner = egg::Runner::<_, _, ()>::new(ExprAnalysis::default())
+        .with_expr(&plan)
+        .run(&rules);
+    assert!(runner.egraph.analysis.conditions.is_empty());
+}
+
+#[test]
+fn no_trace() {
+    let plan = "(proj (list a) (scan t (list a b)))".parse().unwrap();
+    let (_, report) = Optimizer::new().optimize(&plan).unwrap();
+    assert!(report.trace.is_empty());
+}
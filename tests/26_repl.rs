@@ -0,0 +1,85 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn optimize() {
+    let output = repl(
+        "
+        .explain on
+        (proj (list a)
+            (filter (= a 1) (scan t (list a b))))
+        ",
+    );
+    assert!(
+        output.starts_with(
+            "\
+Filter a = 1 (rows=100, cost=5000)
+  Scan t [a] (rows=1000, cost=2000)
+cost: "
+        ),
+        "{output}"
+    );
+    assert!(output.contains("stage projection-pushdown: "), "{output}");
+}
+
+#[test]
+fn rules() {
+    let output = repl(
+        "
+        .rules only cancel
+        .rules
+        (filter true (scan t (list a)))
+        ",
+    );
+    assert!(output.starts_with("[ ] expr (") && output.contains("[x] cancel ("));
+    assert!(
+        output.contains("(scan t (list a))\ncost: 3000 -> 2000\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn errors() {
+    let output = repl(
+        "
+        .rules enable foo
+        .limits node x
+        SELECT a FROM t
+        (proj (list c) (scan t (list a)))
+        .limits iter 1
+        .limits time -1
+        .limits time inf
+        .limits time NaN
+        .limits
+        ",
+    );
+    assert_eq!(
+        output,
+        "\
+error: unknown rule group foo, see .rules
+error: invalid number x
+error: SQL is not supported, enter a plan as an S-expression
+error: invalid plan: column c not found at [0, 0]
+error: invalid time -1, expect a non-negative number
+error: invalid time inf, expect a non-negative number
+error: invalid time NaN, expect a non-negative number
+node: 10000
+iter: 1
+time: 5s
+"
+    );
+}
+
+/// Runs the REPL with the input and returns its output.
+fn repl(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_repl"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    (child.stdin.take().unwrap())
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -0,0 +1,84 @@
+use sql_optimizer_labs::slt;
+
+#[test]
+fn scripts() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/slt");
+    let mut paths: Vec<_> = (std::fs::read_dir(dir).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    for path in paths {
+        let outcome = slt::run(&std::fs::read_to_string(&path).unwrap());
+        for failure in outcome.failures.iter() {
+            println!("{}:{failure}", path.display());
+        }
+        assert!(outcome.failures.is_empty(), "{} failed", path.display());
+    }
+}
+
+#[test]
+fn failures() {
+    let outcome = slt::run(
+        "\
+table t (t.a)
+1
+2
+
+query
+(filter (> t.a 1) (scan t (list t.a)))
+---- result
+1
+
+query
+(scan t (list t.b))
+---- result
+",
+    );
+    let failures: Vec<_> = outcome.failures.iter().map(|f| f.to_string()).collect();
+    assert_eq!(
+        failures,
+        [
+            "line 5: result mismatch\n[expected]\n1\n[actual]\n2",
+            "line 10: unexpected error: column t.b not found",
+        ]
+    );
+}
+
+#[test]
+fn override_output() {
+    let outcome = slt::run(
+        "\
+# a new case
+table t (t.a t.b)
+1 'x y'
+
+query
+(proj (list t.b) (filter (= t.a 1) (scan t (list t.a t.b))))
+
+query
+(proj (list t.c) (scan t (list t.a)))
+---- plan
+(scan t (list t.a))
+",
+    );
+    assert_eq!(
+        outcome.output,
+        "\
+# a new case
+table t (t.a t.b)
+1 'x y'
+
+query
+(proj (list t.b) (filter (= t.a 1) (scan t (list t.a t.b))))
+---- plan
+(proj (list t.b) (filter (= t.a 1) (scan t (list t.a t.b))))
+---- result
+'x y'
+
+query
+(proj (list t.c) (scan t (list t.a)))
+---- error
+invalid plan: column t.c not found at [0, 0]
+"
+    );
+}
@@ -0,0 +1,61 @@
+use sql_optimizer_labs::param::{self, bind, params};
+use sql_optimizer_labs::*;
+
+#[test]
+fn parse() {
+    let plan: RecExpr = "(filter (= a $1) (scan t (list a b)))".parse().unwrap();
+    assert_eq!(plan.to_string(), "(filter (= a $1) (scan t (list a b)))");
+    assert_eq!(plan[1.into()], Expr::Param(Param(1)));
+    assert_eq!(params(&plan), [Param(1)]);
+}
+
+#[test]
+fn optimize() {
+    // parameters are not folded, but the plan around them is still optimized
+    let plan: RecExpr = "
+    (proj (list a)
+        (filter (and (= a $1) (> b $2))
+            (join inner true
+                (scan t1 (list a))
+                (scan t2 (list b)))))"
+        .parse()
+        .unwrap();
+    let (best, _) = Optimizer::new().optimize(&plan).unwrap();
+    assert_eq!(
+        best.to_string(),
+        "(nljoin inner true (proj list (filter (> b $2) (scan t2 (list b)))) (filter (= a $1) (scan t1 (list a))))"
+    );
+    assert_eq!(params(&best), [Param(1), Param(2)]);
+}
+
+#[test]
+fn bind_and_fold() {
+    let plan: RecExpr = "
+    (proj (list (+ a (* $2 2)))
+        (filter (and (= a (+ $1 1)) (isnull $2))
+            (scan t (list a))))"
+        .parse()
+        .unwrap();
+    let bound = bind(&plan, &[Value::Int(1), Value::Int(3)]).unwrap();
+    assert_eq!(
+        bound.to_string(),
+        "(proj (list (+ a 6)) (filter (and (= a 2) false) (scan t (list a))))"
+    );
+    let bound = bind(&plan, &[Value::Int(1), Value::Null]).unwrap();
+    assert_eq!(
+        bound.to_string(),
+        "(proj (list (+ a null)) (filter (and (= a 2) true) (scan t (list a))))"
+    );
+}
+
+#[test]
+fn not_bound() {
+    let plan: RecExpr = "(filter (= a $2) (scan t (list a)))".parse().unwrap();
+    let error = bind(&plan, &[Value::Int(1)]).unwrap_err();
+    assert_eq!(error, param::Error::NotBound(Param(2)));
+    assert_eq!(error.to_string(), "no value for parameter $2");
+    assert_eq!(
+        Error::from(error).to_string(),
+        "invalid parameters: no value for parameter $2"
+    );
+}
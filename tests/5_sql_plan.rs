@@ -7,6 +7,9 @@ fn aggregations() {
     assert_parse_expr("(sum a)");
     assert_parse_expr("(avg a)");
     assert_parse_expr("(count a)");
+    assert_parse_expr("rowcount");
+    assert_parse_expr("(count-distinct a)");
+    assert_parse_expr("(sum-distinct a)");
 }
 
 #[test]
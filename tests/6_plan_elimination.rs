@@ -95,3 +95,28 @@ egg::test_fn! {
     "(proj (list a)
         (scan t (list a b)))"
 }
+
+egg::test_fn! {
+    order_on_sorted,
+    rules(),
+    "(order (list (asc a))
+        (filter (= b 1)
+            (order (list (asc a) (desc b))
+                (scan t (list a b)))))" =>
+    "(filter (= b 1)
+        (order (list (asc a) (desc b))
+            (scan t (list a b))))"
+}
+
+egg::test_fn! {
+    topn_on_sorted,
+    rules(),
+    "(topn 10 0 (list (asc a))
+        (proj (list a)
+            (order (list (asc a) (asc b))
+                (scan t (list a b)))))" =>
+    "(limit 10 0
+        (proj (list a)
+            (order (list (asc a) (asc b))
+                (scan t (list a b)))))"
+}
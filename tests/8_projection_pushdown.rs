@@ -43,6 +43,57 @@ egg::test_fn! {
     ))"
 }
 
+egg::test_fn! {
+    hash_join,
+    projection_pushdown_rules(),
+    // SELECT b FROM t1(a, b, c, d) JOIN t2(x, y, z, w) ON a = x
+    "
+    (proj (list b)
+        (hashjoin inner (list a) (list x)
+            (scan t1 (list a b c d))
+            (scan t2 (list x y z w))
+    ))" => "
+    (proj (list b)
+        (hashjoin inner (list a) (list x)
+            (scan t1 (list a b))
+            (scan t2 (list x))
+    ))"
+}
+
+egg::test_fn! {
+    merge_join,
+    projection_pushdown_rules(),
+    // SELECT b FROM t1(a, b, c, d) JOIN t2(x, y, z, w) ON a = x
+    "
+    (proj (list b)
+        (mergejoin inner (list a) (list x)
+            (order (list (asc a)) (scan t1 (list a b c d)))
+            (order (list (asc x)) (scan t2 (list x y z w)))
+    ))" => "
+    (proj (list b)
+        (mergejoin inner (list a) (list x)
+            (order (list (asc a)) (scan t1 (list a b)))
+            (order (list (asc x)) (scan t2 (list x)))
+    ))"
+}
+
+egg::test_fn! {
+    nested_loop_join,
+    projection_pushdown_rules(),
+    // SELECT b FROM t1(a, b, c, d) JOIN t2(x, y, z, w) ON a > x
+    "
+    (proj (list b)
+        (nljoin inner (> a x)
+            (scan t1 (list a b c d))
+            (scan t2 (list x y z w))
+    ))" => "
+    (proj (list b)
+        (nljoin inner (> a x)
+            (scan t1 (list a b))
+            (scan t2 (list x))
+    ))"
+}
+
 egg::test_fn! {
     agg,
     projection_pushdown_rules(),
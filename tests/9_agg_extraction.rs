@@ -49,6 +49,71 @@ fn agg() {
     });
 }
 
+#[test]
+fn count_star() {
+    // SELECT count(*) FROM t
+    test(Case {
+        select: "(list rowcount)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` rowcount))
+            (order list
+                (filter true
+                    (agg (list rowcount) list
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
+#[test]
+fn distinct_agg() {
+    // SELECT a, count(DISTINCT b), sum(c) FROM t GROUP BY a
+    test(Case {
+        select: "(list a (count-distinct b) (sum c))",
+        from: "(scan t (list a b c))",
+        where_: "",
+        having: "",
+        groupby: "(list a)",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` a) (` (count (` b))) (` (sum (` (sum c)))))
+            (order list
+                (filter true
+                    (agg (list (count (` b)) (sum (` (sum c)))) (list a)
+                        (agg (list (sum c)) (list a b)
+                            (filter true
+                                (scan t (list a b c))
+        ))))))"),
+    });
+}
+
+#[test]
+fn distinct_agg_without_groupby() {
+    // SELECT count(DISTINCT b), count(*) FROM t
+    // count(*) is not split into a sum, which would be null instead of 0 on an empty table
+    test(Case {
+        select: "(list (count-distinct b) rowcount)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Ok("
+        (proj (list (` (count-distinct b)) (` rowcount))
+            (order list
+                (filter true
+                    (agg (list (count-distinct b) rowcount) list
+                        (filter true
+                            (scan t (list a b))
+        )))))"),
+    });
+}
+
 #[test]
 fn error_agg_in_where() {
     // SELECT a FROM t WHERE sum(a) > 1
@@ -87,7 +152,7 @@ fn error_nested_agg() {
         having: "",
         groupby: "",
         orderby: "",
-        expected: Err(Error::NestedAgg("count".into())),
+        expected: Err(Error::NestedAgg("(count (sum a))".into())),
     });
 }
 
@@ -105,6 +170,41 @@ fn error_column_not_in_agg() {
     });
 }
 
+#[test]
+fn error_expect_list() {
+    // the clauses are not validated before planning
+    test(Case {
+        select: "(sum a)",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "",
+        orderby: "",
+        expected: Err(Error::ExpectList("(sum a)".into())),
+    });
+    test(Case {
+        select: "(list (sum b))",
+        from: "(scan t (list a b))",
+        where_: "",
+        having: "",
+        groupby: "a",
+        orderby: "",
+        expected: Err(Error::ExpectList("a".into())),
+    });
+}
+
+#[test]
+fn error_display() {
+    assert_eq!(
+        Error::NestedAgg("(count (sum a))".into()).to_string(),
+        "aggregate function calls cannot be nested: (count (sum a))"
+    );
+    assert_eq!(
+        sql_optimizer_labs::Error::from(Error::ColumnNotInAgg("b".into())).to_string(),
+        "invalid aggregation: column b must appear in the GROUP BY clause or be used in an aggregate function"
+    );
+}
+
 struct Case {
     select: &'static str,
     from: &'static str,
@@ -151,7 +251,7 @@ fn test(mut case: Case) {
             let actual = get_node(id).build_recexpr(get_node).to_string();
             let expected = case
                 .expected
-                .expect(&format!("expect error, but got: {actual:?}"))
+                .unwrap_or_else(|_| panic!("expect error, but got: {actual:?}"))
                 .parse::<RecExpr>()
                 .unwrap()
                 .to_string();